@@ -0,0 +1,64 @@
+use crate::InfiniteDataWriter;
+
+/// Reads the stream of an `InfiniteDataWriter` with byte granularity, keeping the part of the
+/// last pulled batch that was not consumed yet.
+pub(crate) struct ByteStream {
+    writer: InfiniteDataWriter,
+    batch: [u8; 64],
+    consumed: usize,
+}
+
+impl ByteStream {
+    pub const fn new(writer: InfiniteDataWriter) -> Self {
+        Self {
+            writer,
+            batch: [0; 64],
+            consumed: 64,
+        }
+    }
+
+    /// Fill the whole buffer with the next bytes of the stream
+    pub fn fill(&mut self, mut out: &mut [u8]) {
+        while !out.is_empty() {
+            if self.consumed == self.batch.len() {
+                if out.len() >= self.batch.len() {
+                    // Nothing is kept, so a whole batch goes straight into the output
+                    let (batch, rest) = std::mem::take(&mut out).split_at_mut(self.batch.len());
+                    self.writer
+                        .pull_into(batch.try_into().expect("The split is a batch"));
+                    out = rest;
+                    continue;
+                }
+                self.writer.pull_into(&mut self.batch);
+                self.consumed = 0;
+            }
+
+            let len = out.len().min(self.batch.len() - self.consumed);
+            out[..len].copy_from_slice(&self.batch[self.consumed..self.consumed + len]);
+            self.consumed += len;
+            out = &mut out[len..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_matches_stream() {
+        let expected: Vec<u8> = InfiniteDataWriter::new("abc")
+            .flatten()
+            .take(1000)
+            .collect();
+
+        for chunk_size in [1, 7, 63, 64, 65, 200] {
+            let mut stream = ByteStream::new(InfiniteDataWriter::new("abc"));
+            let mut actual = vec![0; 1000];
+            actual
+                .chunks_mut(chunk_size)
+                .for_each(|chunk| stream.fill(chunk));
+            assert_eq!(actual, expected);
+        }
+    }
+}
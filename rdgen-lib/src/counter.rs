@@ -0,0 +1,330 @@
+use std::io::Cursor;
+
+use blake2::{Blake2b, Digest};
+
+use crate::{Error, InfiniteDataWriter};
+
+const BLOCK_SIZE: usize = 64;
+
+/// The number of bytes used to encode the block index (little-endian) in counter mode.
+/// Changing the width changes the whole stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CounterWidth {
+    Four,
+    #[default]
+    Eight,
+    Sixteen,
+}
+
+impl CounterWidth {
+    /// The number of bytes of the encoded block index
+    pub const fn bytes(&self) -> usize {
+        match self {
+            CounterWidth::Four => 4,
+            CounterWidth::Eight => 8,
+            CounterWidth::Sixteen => 16,
+        }
+    }
+
+    /// The largest block index that can be encoded with this width
+    pub const fn max_block_index(&self) -> u128 {
+        match self {
+            CounterWidth::Four => u32::MAX as u128,
+            CounterWidth::Eight => u64::MAX as u128,
+            CounterWidth::Sixteen => u128::MAX,
+        }
+    }
+}
+
+impl TryFrom<usize> for CounterWidth {
+    type Error = Error;
+
+    fn try_from(bytes: usize) -> Result<Self, Self::Error> {
+        match bytes {
+            4 => Ok(CounterWidth::Four),
+            8 => Ok(CounterWidth::Eight),
+            16 => Ok(CounterWidth::Sixteen),
+            _ => Err(Error::InvalidCounterWidth(bytes)),
+        }
+    }
+}
+
+/// A writer where every batch is computed independently as `Blake2b(seed || index)`, where `seed`
+/// is the Blake2b hash of the provided seed, and `index` is the block index encoded as little-endian
+/// with the configured counter width. Unlike the hash chain of `InfiniteDataWriter`, this allows
+/// seeking to any position of the stream in constant time.
+///
+/// The block index is tracked as a u128, so the counter never wraps around. With a 4-byte counter, the stream
+/// has 2^32 blocks (256 GiB). With an 8-byte counter, it has 2^64 blocks (1 ZiB), and with a 16-byte counter,
+/// 2^128 blocks. Since `seek_to` takes a u64 byte offset, it can reach any of the first 16 EiB of the stream,
+/// and the stream can be read further than that by pulling.
+#[must_use]
+pub struct CounterDataWriter {
+    seed: [u8; 64],
+    counter_width: CounterWidth,
+    position: u128,
+    desired_length: Option<u64>,
+    pulled_length: u64,
+}
+
+impl CounterDataWriter {
+    /// Create a new instance with the given seed.
+    /// If `desired length` is Some(), the output will be limited to that length. If None, the output will only
+    /// end when the counter cannot address more blocks.
+    pub fn new(seed: impl AsRef<[u8]>, desired_length: Option<u64>) -> Self {
+        Self::new_from_stream(Cursor::new(seed.as_ref()), desired_length).expect("Cannot fail")
+    }
+
+    /// Create a new instance with the given stream of data.
+    /// If `desired length` is Some(), the output will be limited to that length. If None, the output will only
+    /// end when the counter cannot address more blocks.
+    pub fn new_from_stream(
+        source: impl std::io::Read,
+        desired_length: Option<u64>,
+    ) -> Result<Self, Error> {
+        let seed = *InfiniteDataWriter::new_from_stream(source)?.seed();
+        Ok(Self::from_seed_bytes(seed, desired_length))
+    }
+
+    /// Create a new instance that uses the given 64 bytes directly in place of the hash of the seed.
+    pub const fn from_seed_bytes(seed: [u8; 64], desired_length: Option<u64>) -> Self {
+        Self {
+            seed,
+            counter_width: CounterWidth::Eight,
+            position: 0,
+            desired_length,
+            pulled_length: 0,
+        }
+    }
+
+    /// Use the given width to encode the block index. The default is 8 bytes.
+    pub fn with_counter_width(mut self, counter_width: CounterWidth) -> Self {
+        self.counter_width = counter_width;
+        self
+    }
+
+    pub const fn counter_width(&self) -> CounterWidth {
+        self.counter_width
+    }
+
+    /// The number of bytes of the stream with the configured counter width,
+    /// or None if it does not fit in a u128, which is the case with a 16-byte counter.
+    pub const fn max_stream_length(&self) -> Option<u128> {
+        match self.counter_width.max_block_index().checked_add(1) {
+            Some(blocks) => blocks.checked_mul(BLOCK_SIZE as u128),
+            None => None,
+        }
+    }
+
+    /// The byte offset in the stream of the next byte to be pulled
+    pub const fn position(&self) -> u128 {
+        self.position
+    }
+
+    /// Move to the given byte offset in the stream. The desired length, if any, counts the bytes pulled
+    /// regardless of the position they were pulled from.
+    pub fn seek_to(&mut self, offset: u64) -> Result<(), Error> {
+        if let Some(max) = self.max_stream_length() {
+            if offset as u128 > max {
+                return Err(Error::OffsetOutOfRange(offset, max));
+            }
+        }
+        self.position = offset as u128;
+        Ok(())
+    }
+
+    /// Compute the block with the given index. Blocks are the batches of 64 bytes of the stream.
+    ///
+    /// # Panics
+    /// If `index` is larger than the `max_block_index` of the counter width.
+    pub fn block(&self, index: u128) -> [u8; 64] {
+        assert!(
+            index <= self.counter_width.max_block_index(),
+            "Block index {index} cannot be encoded with a counter of {} bytes",
+            self.counter_width.bytes()
+        );
+
+        let encoded_index = index.to_le_bytes();
+
+        let mut hasher = Blake2b::new();
+        hasher.update(self.seed.as_ref());
+        hasher.update(&encoded_index[..self.counter_width.bytes()]);
+        hasher.finalize().into()
+    }
+
+    /// The byte at the given offset of the stream, which is computed from its block alone, in constant time.
+    ///
+    /// # Panics
+    /// If the offset is beyond the stream of the counter width.
+    pub fn byte_at(&self, offset: u64) -> u8 {
+        let offset = offset as u128;
+        self.block(offset / BLOCK_SIZE as u128)[(offset % BLOCK_SIZE as u128) as usize]
+    }
+
+    /// Pull the data from the current position until the end of its block, limited by the desired length.
+    pub fn pull(&mut self) -> Vec<u8> {
+        let mut data = [0; BLOCK_SIZE];
+        let length = self.pull_into(&mut data);
+        data[..length].to_vec()
+    }
+
+    /// Same as `pull`, but writes the data into the start of the given buffer, and returns its length,
+    /// which is zero at the end of the stream.
+    pub fn pull_into(&mut self, out: &mut [u8; 64]) -> usize {
+        let block_index = self.position / BLOCK_SIZE as u128;
+        if block_index > self.counter_width.max_block_index() {
+            return 0;
+        }
+
+        let start = (self.position % BLOCK_SIZE as u128) as usize;
+        let max_length_to_push = match self.desired_length {
+            Some(l) => (l - self.pulled_length).min((BLOCK_SIZE - start) as u64) as usize,
+            None => BLOCK_SIZE - start,
+        };
+        if max_length_to_push == 0 {
+            return 0;
+        }
+
+        let block = self.block(block_index);
+        out[..max_length_to_push].copy_from_slice(&block[start..start + max_length_to_push]);
+        self.pulled_length += max_length_to_push as u64;
+        self.position += max_length_to_push as u128;
+        max_length_to_push
+    }
+}
+
+/// The byte at the given offset of the stream of `CounterDataWriter` with the given seed and the default
+/// counter width, in constant time, for spot checks that do not need the rest of the stream.
+pub fn byte_at(seed: impl AsRef<[u8]>, offset: u64) -> u8 {
+    CounterDataWriter::new(seed, None).byte_at(offset)
+}
+
+impl Iterator for CounterDataWriter {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.pull();
+        if data.is_empty() {
+            None
+        } else {
+            Some(data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(writer: CounterDataWriter) -> Vec<u8> {
+        writer.into_iter().fold(Vec::new(), |mut so_far, curr| {
+            so_far.extend(curr);
+            so_far
+        })
+    }
+
+    #[test]
+    fn vectors_per_width() {
+        let writer =
+            CounterDataWriter::new("abc", Some(100)).with_counter_width(CounterWidth::Four);
+        assert_eq!(hex::encode(collect(writer)), "223e7dc127b99e91a9f1cd061736f918209ad3e8d7eb43b3bb3f3c0c6f3f7d65bdf31f976db4d64e18b1c552d2d0eed2abb4d4b7ff4b27b001b3b6448c840675044561aa575c85354b53c58546449ecc89b5aab48eb7398e917487fafae2cb95b0fe76c6");
+
+        let writer = CounterDataWriter::new("abc", Some(100));
+        assert_eq!(writer.counter_width(), CounterWidth::Eight);
+        assert_eq!(hex::encode(collect(writer)), "e1604fa6ef127c1179e4a3f6d85b6ef4f3576574aa1c9406873df74cdaab3bdeb47fe5ce39fdb6d3637d0cebfa27c7154621a549ed75f7734c3a3368ead19539602a03264b6578d2db0a34e34d0e297b36f0672d499ddd3f68fc1f18e62a1426f33ab3df");
+
+        let writer =
+            CounterDataWriter::new("abc", Some(100)).with_counter_width(CounterWidth::Sixteen);
+        assert_eq!(hex::encode(collect(writer)), "cd4297c4d4a2f84bed6b982a23afca13b39e53d11bb6d10829e6b953cd741566f96588d736a3644ff5fa43c6d1af75d6db9fe46cbf0e0204c8c8beb9a089c953c489865608477b47c08feb7e2f73411bba894e90d792c498aa08ac16f4fd40351edf595b");
+    }
+
+    #[test]
+    fn seek_matches_full_stream() {
+        let expected = collect(CounterDataWriter::new("abc", Some(1000)));
+
+        for offset in [0, 1, 63, 64, 65, 500, 999] {
+            let mut writer = CounterDataWriter::new("abc", Some(1000 - offset));
+            writer.seek_to(offset).unwrap();
+            assert_eq!(collect(writer), expected[offset as usize..]);
+        }
+    }
+
+    #[test]
+    fn byte_at_matches_stream() {
+        let stream = collect(CounterDataWriter::new("abc", Some(1000)));
+        for offset in [0, 1, 63, 64, 65, 500, 999] {
+            assert_eq!(byte_at("abc", offset), stream[offset as usize]);
+        }
+
+        let mut writer = CounterDataWriter::new("abc", Some(1));
+        let offset = 1 << 40;
+        writer.seek_to(offset).unwrap();
+        assert_eq!(byte_at("abc", offset), writer.pull()[0]);
+
+        let writer = CounterDataWriter::new("abc", None).with_counter_width(CounterWidth::Four);
+        let stream = collect(
+            CounterDataWriter::new("abc", Some(200)).with_counter_width(CounterWidth::Four),
+        );
+        assert_eq!(writer.byte_at(150), stream[150]);
+    }
+
+    #[test]
+    fn seek_out_of_range() {
+        let mut writer = CounterDataWriter::new("abc", None).with_counter_width(CounterWidth::Four);
+        let max = 64 * (u32::MAX as u64 + 1);
+        writer.seek_to(max).unwrap();
+        assert!(writer.pull().is_empty());
+        assert!(writer.seek_to(max + 1).is_err());
+    }
+
+    #[test]
+    fn no_aliasing_at_4_gib_blocks() {
+        let boundary = 1u64 << 32;
+
+        for width in [CounterWidth::Eight, CounterWidth::Sixteen] {
+            let mut writer = CounterDataWriter::new("abc", Some(128)).with_counter_width(width);
+            writer.seek_to((boundary - 1) * 64).unwrap();
+            let before_boundary = writer.pull();
+            let after_boundary = writer.pull();
+            assert!(writer.pull().is_empty());
+
+            assert_eq!(before_boundary, writer.block(boundary as u128 - 1));
+            assert_eq!(after_boundary, writer.block(boundary as u128));
+            assert_ne!(after_boundary, writer.block(0));
+            assert_ne!(after_boundary, writer.block(1));
+            assert_eq!(writer.position(), (boundary as u128 + 1) * 64);
+        }
+    }
+
+    #[test]
+    fn max_stream_lengths() {
+        let writer = CounterDataWriter::new("abc", None);
+        assert_eq!(writer.max_stream_length(), Some(1 << 70));
+        let writer = writer.with_counter_width(CounterWidth::Four);
+        assert_eq!(writer.max_stream_length(), Some(1 << 38));
+        let mut writer = writer.with_counter_width(CounterWidth::Sixteen);
+        assert_eq!(writer.max_stream_length(), None);
+
+        writer.seek_to(u64::MAX).unwrap();
+        assert_eq!(writer.pull().len(), 1);
+        assert_eq!(writer.pull().len(), 64);
+        assert_eq!(writer.position(), u64::MAX as u128 + 65);
+    }
+
+    #[test]
+    fn from_seed_bytes_skips_hashing() {
+        let seed = *InfiniteDataWriter::new("abc").seed();
+        assert_eq!(
+            collect(CounterDataWriter::from_seed_bytes(seed, Some(100))),
+            collect(CounterDataWriter::new("abc", Some(100)))
+        );
+    }
+
+    #[test]
+    fn counter_width_from_bytes() {
+        assert_eq!(CounterWidth::try_from(4).unwrap(), CounterWidth::Four);
+        assert_eq!(CounterWidth::try_from(8).unwrap(), CounterWidth::Eight);
+        assert_eq!(CounterWidth::try_from(16).unwrap(), CounterWidth::Sixteen);
+        assert!(CounterWidth::try_from(2).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::{io::Cursor, num::NonZeroUsize};
+
+use blake2::digest::{Digest, Output};
+
+use crate::Error;
+
+/// Hash the whole stream of data with the given hash function.
+pub(crate) fn hash_stream<D: Digest>(source: impl std::io::Read) -> Result<Output<D>, Error> {
+    let mut seed_hasher = D::new();
+    update_from_stream(source, |data| seed_hasher.update(data))?;
+    Ok(seed_hasher.finalize())
+}
+
+/// Feed the whole stream of data to `update`, chunk by chunk.
+pub(crate) fn update_from_stream(
+    mut source: impl std::io::Read,
+    mut update: impl FnMut(&[u8]),
+) -> Result<(), Error> {
+    let mut buffer = [0; 4096];
+
+    loop {
+        let bytes_read = source
+            .read(&mut buffer)
+            .map_err(|e| Error::DataStreamError(e.to_string()))?;
+
+        if bytes_read == 0 {
+            break;
+        }
+
+        update(&buffer[..bytes_read]);
+    }
+
+    Ok(())
+}
+
+/// The same hash chain as `InfiniteDataWriter`, with any hash function instead of Blake2b.
+/// Every batch has the output size of the hash function, so different hash functions produce
+/// completely different streams.
+#[derive(Clone)]
+#[must_use]
+pub struct DigestDataWriter<D: Digest> {
+    seed: Output<D>,
+}
+
+impl<D: Digest> DigestDataWriter<D> {
+    /// Create a new instance with the given seed.
+    pub fn new(seed: impl AsRef<[u8]>) -> Self {
+        Self::new_from_stream(Cursor::new(seed.as_ref())).expect("Cannot fail")
+    }
+
+    /// Create a new instance with the given stream of data.
+    pub fn new_from_stream(source: impl std::io::Read) -> Result<Self, Error> {
+        Ok(Self {
+            seed: hash_stream::<D>(source)?,
+        })
+    }
+
+    /// Pull a batch of data, and generate new data in seed
+    pub fn pull(&mut self) -> Output<D> {
+        let mut seed = D::digest(&self.seed);
+        std::mem::swap(&mut seed, &mut self.seed);
+        seed
+    }
+
+    /// Fork the stream deterministically by updating the internal seed to the hash of `seed || extra`,
+    /// like `InfiniteDataWriter::mix_in`, with the hash function of this writer.
+    pub fn mix_in(&mut self, extra: &[u8]) {
+        self.seed = D::new()
+            .chain_update(&self.seed)
+            .chain_update(extra)
+            .finalize();
+    }
+
+    /// The size of every batch, which is the output size of the hash function
+    pub fn batch_size(&self) -> NonZeroUsize {
+        NonZeroUsize::new(<D as Digest>::output_size()).expect("Size must be larger than zero")
+    }
+}
+
+impl<D: Digest> Iterator for DigestDataWriter<D> {
+    type Item = Output<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.pull())
+    }
+}
+
+/// The hash chain with SHA-256, where every batch is 32 bytes
+#[cfg(feature = "sha256")]
+pub type Sha256DataWriter = DigestDataWriter<sha2::Sha256>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::InfiniteDataWriter;
+
+    #[test]
+    fn blake2b_matches_infinite_writer() {
+        let mut expected = InfiniteDataWriter::new("abc");
+        let mut writer = DigestDataWriter::<blake2::Blake2b512>::new("abc");
+        assert_eq!(writer.batch_size().get(), 64);
+        for _ in 0..10 {
+            assert_eq!(writer.pull().as_slice(), expected.pull());
+        }
+    }
+
+    #[test]
+    fn blake2b_mix_in_matches_infinite_writer() {
+        let mut expected = InfiniteDataWriter::new("abc");
+        expected.mix_in(b"x");
+        let mut writer = DigestDataWriter::<blake2::Blake2b512>::new("abc");
+        writer.mix_in(b"x");
+        assert_eq!(writer.pull().as_slice(), expected.pull());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn sha256_vector() {
+        let writer = Sha256DataWriter::new("abc");
+        assert_eq!(writer.batch_size().get(), 32);
+        let data: Vec<u8> = writer.flatten().take(100).collect();
+        assert_eq!(hex::encode(data), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358f2a778f1a6ed3d5bc59a5d79104c598f3f07093f240ca4e91333fb09ed4f36daebea187d");
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{Error, FiniteDataWriter};
+
+/// The default largest length that `generate` holds in memory: 1 GiB
+pub const DEFAULT_GENERATE_LIMIT: u64 = 1 << 30;
+
+/// The environment variable that overrides the default limit of `generate`, as a number of bytes
+pub const GENERATE_LIMIT_VAR: &str = "RDGEN_GENERATE_LIMIT";
+
+/// Generate `length` bytes with the given seed into a single buffer, with the limit of `MemoryGenerator::new`.
+/// Lengths above the limit fail with `Error::LengthAboveLimit`, instead of an allocation that may abort the
+/// program. For large lengths, stream the data with `FiniteDataWriter` instead.
+pub fn generate(seed: impl AsRef<[u8]>, length: u64) -> Result<Vec<u8>, Error> {
+    MemoryGenerator::new().generate(seed, length)
+}
+
+/// Generates data into a single buffer, up to a soft limit on its length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct MemoryGenerator {
+    limit: u64,
+}
+
+impl MemoryGenerator {
+    /// Create a new instance with the limit in the environment variable `RDGEN_GENERATE_LIMIT`, or
+    /// `DEFAULT_GENERATE_LIMIT` if the variable is not set or is not a number.
+    pub fn new() -> Self {
+        Self {
+            limit: limit_from(std::env::var(GENERATE_LIMIT_VAR).ok().as_deref()),
+        }
+    }
+
+    /// Use the given limit, in bytes, instead of the one of the environment
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub const fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Generate `length` bytes with the given seed into a single buffer. The length is checked against
+    /// the limit before anything is allocated, and a failed allocation is an error too.
+    pub fn generate(&self, seed: impl AsRef<[u8]>, length: u64) -> Result<Vec<u8>, Error> {
+        if length > self.limit {
+            return Err(Error::LengthAboveLimit(length, self.limit));
+        }
+        let capacity = usize::try_from(length).map_err(|_| Error::AllocationFailed(length))?;
+
+        let mut data = Vec::new();
+        data.try_reserve_exact(capacity)
+            .map_err(|_| Error::AllocationFailed(length))?;
+        FiniteDataWriter::new(seed, Some(length)).for_each(|chunk| data.extend(chunk));
+        Ok(data)
+    }
+}
+
+impl Default for MemoryGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The limit given by the value of the environment variable, if it is a number
+fn limit_from(value: Option<&str>) -> u64 {
+    value
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_GENERATE_LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_stream() {
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+        let generator = MemoryGenerator::new().with_limit(1000);
+        assert_eq!(generator.generate("abc", 1000).unwrap(), expected);
+        assert!(generator.generate("abc", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn oversized_request_fails() {
+        let generator = MemoryGenerator::new().with_limit(1000);
+        assert!(matches!(
+            generator.generate("abc", 1001),
+            Err(Error::LengthAboveLimit(1001, 1000))
+        ));
+
+        // Way too large to allocate, which must fail without trying to
+        let generator = MemoryGenerator::new().with_limit(u64::MAX);
+        assert!(matches!(
+            generator.generate("abc", u64::MAX),
+            Err(Error::AllocationFailed(u64::MAX))
+        ));
+    }
+
+    #[test]
+    fn limit_from_environment() {
+        assert_eq!(limit_from(None), DEFAULT_GENERATE_LIMIT);
+        assert_eq!(limit_from(Some("12345")), 12345);
+        assert_eq!(limit_from(Some("lots")), DEFAULT_GENERATE_LIMIT);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{byte_stream::ByteStream, InfiniteDataWriter};
+
+/// A reproducible body for streaming HTTP responses: the first `length` bytes of the stream of the seed,
+/// in chunks of `chunk` bytes, where only the last chunk may be shorter. The chunks are generated
+/// as they are iterated over, so the body is never held in memory as a whole.
+///
+/// # Panics
+/// If `chunk` is zero.
+pub fn http_body(
+    seed: impl AsRef<[u8]>,
+    length: u64,
+    chunk: usize,
+) -> impl Iterator<Item = Vec<u8>> + Send {
+    assert!(chunk > 0, "Chunk size must be larger than zero");
+
+    let mut stream = ByteStream::new(InfiniteDataWriter::new(seed));
+    let mut remaining = length;
+    std::iter::from_fn(move || {
+        if remaining == 0 {
+            return None;
+        }
+        let mut piece = vec![0; remaining.min(chunk as u64) as usize];
+        stream.fill(&mut piece);
+        remaining -= piece.len() as u64;
+        Some(piece)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FiniteDataWriter;
+
+    #[test]
+    fn chunks_concatenate_to_stream() {
+        for (length, chunk) in [(0, 10), (1000, 1), (1000, 100), (1000, 333), (1000, 5000)] {
+            let chunks: Vec<Vec<u8>> = http_body("abc", length, chunk).collect();
+            assert_eq!(chunks.len() as u64, length.div_ceil(chunk as u64));
+            assert!(chunks.iter().rev().skip(1).all(|c| c.len() == chunk));
+
+            let body: Vec<u8> = chunks.concat();
+            let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(length))
+                .flatten()
+                .collect();
+            assert_eq!(body, expected);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{byte_stream::ByteStream, InfiniteDataWriter};
+
+/// An infinite stream that alternates blocks of two streams, created with `interleave`.
+#[must_use]
+pub struct Interleave {
+    streams: [ByteStream; 2],
+    block: usize,
+    next: usize,
+}
+
+/// Create a stream that alternates blocks of `block` bytes from the streams of two seeds:
+/// the first `block` bytes of the stream of `seed_a`, then the first `block` bytes of the stream of `seed_b`,
+/// then the next `block` bytes of the stream of `seed_a`, and so on. Every item of the iterator is one block.
+///
+/// # Panics
+/// If `block` is zero.
+pub fn interleave(seed_a: impl AsRef<[u8]>, seed_b: impl AsRef<[u8]>, block: usize) -> Interleave {
+    interleave_writers(
+        InfiniteDataWriter::new(seed_a),
+        InfiniteDataWriter::new(seed_b),
+        block,
+    )
+}
+
+/// Same as `interleave`, but with writers that are already created, for example from streams.
+///
+/// # Panics
+/// If `block` is zero.
+pub fn interleave_writers(
+    writer_a: InfiniteDataWriter,
+    writer_b: InfiniteDataWriter,
+    block: usize,
+) -> Interleave {
+    assert!(block > 0, "Interleaved block size must be larger than zero");
+
+    Interleave {
+        streams: [ByteStream::new(writer_a), ByteStream::new(writer_b)],
+        block,
+        next: 0,
+    }
+}
+
+impl Iterator for Interleave {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut data = vec![0; self.block];
+        self.streams[self.next].fill(&mut data);
+        self.next = (self.next + 1) % self.streams.len();
+        Some(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_reconstruct_streams() {
+        for block in [1, 10, 64, 100] {
+            let blocks: Vec<Vec<u8>> = interleave("abc", "xyz", block).take(20).collect();
+            assert!(blocks.iter().all(|b| b.len() == block));
+
+            let a: Vec<u8> = blocks.iter().step_by(2).flatten().copied().collect();
+            let b: Vec<u8> = blocks
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .flatten()
+                .copied()
+                .collect();
+
+            let expected_a: Vec<u8> = InfiniteDataWriter::new("abc")
+                .flatten()
+                .take(10 * block)
+                .collect();
+            let expected_b: Vec<u8> = InfiniteDataWriter::new("xyz")
+                .flatten()
+                .take(10 * block)
+                .collect();
+            assert_eq!(a, expected_a);
+            assert_eq!(b, expected_b);
+        }
+    }
+
+    #[test]
+    fn known_prefix() {
+        let data: Vec<u8> = interleave("abc", "abc", 3).take(4).flatten().collect();
+        assert_eq!(hex::encode(data), "ba80a5ba80a53f981c3f981c");
+    }
+}
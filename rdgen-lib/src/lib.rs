@@ -1,53 +1,133 @@
 use std::{io::Cursor, num::NonZeroUsize};
 
-use blake2::{Blake2b, Digest};
+use blake2::{Blake2b, Blake2b512, Digest};
+use byte_stream::ByteStream;
+
+mod byte_stream;
+mod counter;
+mod derive;
+mod digest_writer;
+mod generate;
+#[cfg(feature = "http")]
+mod http_body;
+mod interleave;
+mod records;
+#[cfg(feature = "rand")]
+mod rng;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod variable;
+
+pub use blake2::digest;
+pub use counter::{byte_at, CounterDataWriter, CounterWidth};
+pub use derive::derive_seeds;
+pub use digest_writer::DigestDataWriter;
+#[cfg(feature = "sha256")]
+pub use digest_writer::Sha256DataWriter;
+pub use generate::{generate, MemoryGenerator, DEFAULT_GENERATE_LIMIT, GENERATE_LIMIT_VAR};
+#[cfg(feature = "http")]
+pub use http_body::http_body;
+pub use interleave::{interleave, interleave_writers, Interleave};
+pub use records::records;
+#[cfg(feature = "rand")]
+pub use rng::DataWriterRng;
+pub use variable::VariableDataWriter;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Error while reading data stream: `{0}`")]
     DataStreamError(String),
+    #[error("Invalid counter width: {0} bytes. Supported widths are 4, 8 and 16 bytes")]
+    InvalidCounterWidth(usize),
+    #[error("Offset {0} is beyond the maximum addressable length of the stream: {1}")]
+    OffsetOutOfRange(u64, u128),
+    #[error("Invalid digest size: {0} bytes. Supported sizes are 1 to 64 bytes")]
+    InvalidDigestSize(usize),
+    #[error("Generating {0} bytes in memory is above the limit of {1} bytes. Stream the data with FiniteDataWriter instead, or raise the limit")]
+    LengthAboveLimit(u64, u64),
+    #[error("Allocating {0} bytes for the generated data failed. Stream the data with FiniteDataWriter instead")]
+    AllocationFailed(u64),
+    #[error("The writer has no desired length, so its output cannot be collected")]
+    UnboundedLength,
 }
 
 #[must_use]
 pub struct InfiniteDataWriter {
     seed: [u8; 64],
+    /// The last batch pulled with `pull_ref`
+    batch: [u8; 64],
 }
 
 impl InfiniteDataWriter {
     /// Create a new instance with the given seed.
+    /// An empty seed is valid, and its internal seed is the Blake2b hash of the empty string.
     pub fn new(seed: impl AsRef<[u8]>) -> Self {
         Self::new_from_stream(Cursor::new(seed.as_ref())).expect("Cannot fail")
     }
 
     /// Create a new instance with the given stream of data.
-    pub fn new_from_stream(mut source: impl std::io::Read) -> Result<Self, Error> {
-        let mut seed_hasher = Blake2b::new();
-
-        let mut buffer = [0; 4096];
-
-        loop {
-            let bytes_read = source
-                .read(&mut buffer)
-                .map_err(|e| Error::DataStreamError(e.to_string()))?;
-
-            if bytes_read == 0 {
-                break;
-            }
+    pub fn new_from_stream(source: impl std::io::Read) -> Result<Self, Error> {
+        let seed = digest_writer::hash_stream::<Blake2b512>(source)?.into();
+        Ok(Self::from_seed_bytes(seed))
+    }
 
-            seed_hasher.update(&buffer[..bytes_read]);
+    /// Create a new instance that uses the given 64 bytes directly as the internal seed.
+    /// Unlike `new`, which hashes the seed with Blake2b first, this does no hashing, so a seed
+    /// obtained from `seed()` restores the exact state of the writer it was taken from.
+    pub const fn from_seed_bytes(seed: [u8; 64]) -> Self {
+        Self {
+            seed,
+            batch: [0; 64],
         }
+    }
 
-        let seed = seed_hasher.finalize().into();
-        Ok(Self { seed })
+    /// The current internal seed, which is also the next batch to be pulled.
+    pub const fn seed(&self) -> &[u8; 64] {
+        &self.seed
     }
 
     /// Pull a batch of data, and generate new data in seed
     pub fn pull(&mut self) -> [u8; 64] {
+        let mut batch = [0; 64];
+        self.pull_into(&mut batch);
+        batch
+    }
+
+    /// Same as `pull`, but writes the batch into the given buffer
+    pub fn pull_into(&mut self, out: &mut [u8; 64]) {
+        Self::advance(&mut self.seed, out);
+    }
+
+    /// Same as `pull`, but returns the batch as a borrow of a buffer inside the writer, which avoids copying it.
+    /// The buffer is overwritten by the next call, which the borrow checker enforces: the returned reference
+    /// borrows the writer mutably, so it must be dropped before the writer can be used again.
+    pub fn pull_ref(&mut self) -> &[u8; 64] {
+        let Self { seed, batch } = self;
+        Self::advance(seed, batch);
+        batch
+    }
+
+    /// The step of the hash chain: replace the seed with its hash, and write the previous seed, which is the
+    /// batch, into `out`
+    fn advance(seed: &mut [u8; 64], out: &mut [u8; 64]) {
+        let mut hasher = Blake2b::new();
+        hasher.update(seed.as_ref());
+        *out = hasher.finalize().into();
+        std::mem::swap(out, seed);
+    }
+
+    /// Skip the next batch, without returning it, for callers that work at batch granularity
+    pub fn skip_batch(&mut self) {
+        self.pull_ref();
+    }
+
+    /// Fork the stream deterministically by updating the internal seed to `Blake2b(seed || extra)`.
+    /// The batches pulled after this follow a new branch, which only depends on the state and `extra`.
+    pub fn mix_in(&mut self, extra: &[u8]) {
         let mut hasher = Blake2b::new();
         hasher.update(self.seed.as_ref());
-        let mut seed = hasher.finalize().into();
-        std::mem::swap(&mut seed, &mut self.seed);
-        seed
+        hasher.update(extra);
+        self.seed = hasher.finalize().into();
     }
 
     pub const fn batch_size(&self) -> NonZeroUsize {
@@ -68,17 +148,17 @@ impl Iterator for InfiniteDataWriter {
 
 #[must_use]
 pub struct FiniteDataWriter {
-    writer: InfiniteDataWriter,
-    desired_length: Option<usize>,
-    pulled_length: usize,
+    stream: ByteStream,
+    desired_length: Option<u64>,
+    pulled_length: u64,
 }
 
 impl FiniteDataWriter {
     /// Create a new instance with the given seed.
     /// If `desired length` is Some(), the output will be limited to that length. If None, the output will never have an end.
-    pub fn new(seed: impl AsRef<[u8]>, desired_length: Option<usize>) -> Self {
+    pub fn new(seed: impl AsRef<[u8]>, desired_length: Option<u64>) -> Self {
         Self {
-            writer: InfiniteDataWriter::new(seed),
+            stream: ByteStream::new(InfiniteDataWriter::new(seed)),
             desired_length,
             pulled_length: 0,
         }
@@ -88,34 +168,131 @@ impl FiniteDataWriter {
     /// If `desired length` is Some(), the output will be limited to that length. If None, the output will never have an end.
     pub fn new_from_stream(
         source: impl std::io::Read,
-        desired_length: Option<usize>,
+        desired_length: Option<u64>,
     ) -> Result<Self, Error> {
         Ok(Self {
-            writer: InfiniteDataWriter::new_from_stream(source)?,
+            stream: ByteStream::new(InfiniteDataWriter::new_from_stream(source)?),
             desired_length,
             pulled_length: 0,
         })
     }
 
+    /// Create a new instance that uses the given 64 bytes directly as the internal seed, without hashing,
+    /// as with `InfiniteDataWriter::from_seed_bytes`.
+    pub const fn from_seed_bytes(seed: [u8; 64], desired_length: Option<u64>) -> Self {
+        Self {
+            stream: ByteStream::new(InfiniteDataWriter::from_seed_bytes(seed)),
+            desired_length,
+            pulled_length: 0,
+        }
+    }
+
     /// Pull a batch of data, and generate new data in seed
     pub fn pull(&mut self) -> Vec<u8> {
-        let data = self.writer.pull();
+        let mut batch = [0; 64];
+        let length = self.pull_into(&mut batch);
+        batch[..length].to_vec()
+    }
 
-        let desired_length = match self.desired_length {
-            Some(l) => l,
-            None => return data.to_vec(),
+    /// Same as `pull`, but writes the batch into the start of the given buffer, and returns its length,
+    /// which is zero once the desired length has been reached.
+    pub fn pull_into(&mut self, out: &mut [u8; 64]) -> usize {
+        let length = self.fill(out);
+        self.pulled_length += length as u64;
+        length
+    }
+
+    /// Fill the start of the buffer with the next bytes of the output, up to the desired length, and return how many
+    /// were written. This does not count towards the pulled length.
+    fn fill(&mut self, out: &mut [u8]) -> usize {
+        let length = match self.remaining() {
+            Some(remaining) => remaining.min(out.len() as u64) as usize,
+            None => out.len(),
         };
+        self.stream.fill(&mut out[..length]);
+        length
+    }
 
-        let max_length_to_push = desired_length - self.pulled_length;
+    /// Skip the next batch, which counts towards the desired length like a pull, without allocating it
+    pub fn skip_batch(&mut self) {
+        self.pull_into(&mut [0; 64]);
+    }
 
-        if max_length_to_push > self.writer.batch_size().get() {
-            self.pulled_length += self.writer.batch_size().get();
-            data.to_vec()
-        } else {
-            self.pulled_length += max_length_to_push;
-            data.split_at(max_length_to_push).0.to_vec()
+    /// Whether the writer has produced all its desired length, so the next pull is empty.
+    /// Always false for writers without a desired length.
+    pub const fn is_exhausted(&self) -> bool {
+        match self.desired_length {
+            Some(l) => self.pulled_length >= l,
+            None => false,
+        }
+    }
+
+    /// The number of bytes left before the desired length is reached, or None for writers without a desired length
+    pub const fn remaining(&self) -> Option<u64> {
+        match self.desired_length {
+            Some(l) => Some(l.saturating_sub(self.pulled_length)),
+            None => None,
+        }
+    }
+
+    /// Collect the rest of the output into a single buffer, which is allocated once with the remaining length.
+    /// Writers without a desired length fail with `Error::UnboundedLength`, and a failed allocation is an error too.
+    pub fn into_vec(mut self) -> Result<Vec<u8>, Error> {
+        let remaining = self.remaining().ok_or(Error::UnboundedLength)?;
+        let capacity =
+            usize::try_from(remaining).map_err(|_| Error::AllocationFailed(remaining))?;
+
+        let mut data = Vec::new();
+        data.try_reserve_exact(capacity)
+            .map_err(|_| Error::AllocationFailed(remaining))?;
+        let mut batch = [0; 64];
+        loop {
+            let length = self.pull_into(&mut batch);
+            if length == 0 {
+                return Ok(data);
+            }
+            data.extend_from_slice(&batch[..length]);
         }
     }
+
+    /// Write the next `length` bytes of the output, or fewer if the desired length is reached first,
+    /// as lowercase hex into the given writer, such as a formatter, without allocating.
+    /// The rest of the last batch is kept, so the next call or pull continues right after the bytes written.
+    pub fn write_hex_to(
+        &mut self,
+        w: &mut impl std::fmt::Write,
+        length: usize,
+    ) -> std::fmt::Result {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+        let mut batch = [0; 64];
+        let mut hex = [0; 128];
+        let mut remaining = length;
+        while remaining > 0 {
+            let pulled = self.fill(&mut batch[..remaining.min(64)]);
+            if pulled == 0 {
+                break;
+            }
+            self.pulled_length += pulled as u64;
+            for (byte, pair) in batch[..pulled].iter().zip(hex.chunks_exact_mut(2)) {
+                pair[0] = DIGITS[usize::from(byte >> 4)];
+                pair[1] = DIGITS[usize::from(byte & 0xf)];
+            }
+            w.write_str(std::str::from_utf8(&hex[..2 * pulled]).expect("Hex digits are ASCII"))?;
+            remaining -= pulled;
+        }
+        Ok(())
+    }
+
+    /// Iterate over the chunks of data, with the byte offset of the start of every chunk,
+    /// where the first chunk yielded is at offset 0.
+    pub fn offsets(self) -> impl Iterator<Item = (u64, Vec<u8>)> {
+        self.scan(0u64, |offset, chunk| {
+            let start = *offset;
+            *offset += chunk.len() as u64;
+            Some((start, chunk))
+        })
+    }
 }
 
 impl Iterator for FiniteDataWriter {
@@ -131,8 +308,25 @@ impl Iterator for FiniteDataWriter {
     }
 }
 
+/// The number of Blake2b evaluations that the hash chain performs to produce `length` bytes, one per batch of 64
+/// bytes, not counting the hash of the seed. This does not generate any data.
+pub const fn hash_evaluations(length: u64) -> u64 {
+    length.div_ceil(64)
+}
+
+/// Create a `FiniteDataWriter` with the given seed and desired length, as a boxed iterator, for consumers that
+/// store different sources of data as trait objects without naming their concrete types.
+pub fn boxed_iter(
+    seed: impl AsRef<[u8]>,
+    desired_length: Option<u64>,
+) -> Box<dyn Iterator<Item = Vec<u8>> + Send> {
+    Box::new(FiniteDataWriter::new(seed, desired_length))
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -182,7 +376,180 @@ mod tests {
 
         // The data should never end, since size is None
         for _ in 0..100 {
-            assert!(writer.pull().len() > 0);
+            assert!(!writer.pull().is_empty());
+        }
+    }
+
+    #[test]
+    fn from_seed_bytes_restores_checkpoint() {
+        let mut writer = InfiniteDataWriter::new("abc");
+        writer.pull();
+        writer.pull();
+
+        let mut restored = InfiniteDataWriter::from_seed_bytes(*writer.seed());
+        for _ in 0..10 {
+            assert_eq!(restored.pull(), writer.pull());
+        }
+    }
+
+    #[test]
+    fn from_seed_bytes_does_not_hash() {
+        let seed = [7; 64];
+        let mut writer = InfiniteDataWriter::from_seed_bytes(seed);
+        assert_eq!(writer.pull(), seed);
+
+        let mut hashed = InfiniteDataWriter::new(seed);
+        assert_ne!(hashed.pull(), seed);
+    }
+
+    #[test]
+    fn skip_batch_drops_one_batch() {
+        let mut fresh = InfiniteDataWriter::new("abc");
+        fresh.pull();
+        let mut writer = InfiniteDataWriter::new("abc");
+        writer.skip_batch();
+        assert_eq!(writer.pull(), fresh.pull());
+
+        let mut writer = FiniteDataWriter::new("abc", Some(100));
+        writer.skip_batch();
+        assert_eq!(
+            hex::encode(writer.pull()),
+            "66cb547665e462bbdd51d9b6ce1221116e9cfc6711c78d8798158349d12fa8ca513efb14"
+        );
+        writer.skip_batch();
+        assert!(writer.is_exhausted());
+    }
+
+    #[test]
+    fn empty_seed() {
+        let mut writer = FiniteDataWriter::new("", Some(128));
+        assert_eq!(hex::encode(writer.pull()), "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce");
+        assert_eq!(writer.pull().len(), 64);
+        assert_eq!(writer.pull().len(), 0);
+    }
+
+    #[test]
+    fn offsets_follow_chunk_lengths() {
+        let chunks: Vec<_> = FiniteDataWriter::new("abc", Some(150)).offsets().collect();
+        let offsets: Vec<u64> = chunks.iter().map(|(offset, _)| *offset).collect();
+        assert_eq!(offsets, [0, 64, 128]);
+        assert_eq!(chunks[2].1.len(), 22);
+
+        let data: Vec<u8> = chunks.into_iter().flat_map(|(_, chunk)| chunk).collect();
+        assert_eq!(
+            data,
+            FiniteDataWriter::new("abc", Some(150))
+                .flatten()
+                .collect::<Vec<_>>()
+        );
+
+        let mut unbounded = FiniteDataWriter::new("abc", None).offsets();
+        assert_eq!(unbounded.nth(1000).unwrap().0, 64_000);
+    }
+
+    #[test]
+    fn into_vec_collects_remaining_output() {
+        let writer = FiniteDataWriter::new("abc", Some(100));
+        assert_eq!(writer.remaining(), Some(100));
+        let data = writer.into_vec().unwrap();
+        assert_eq!(data.capacity(), 100);
+        assert_eq!(hex::encode(data), "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd400992366cb547665e462bbdd51d9b6ce1221116e9cfc6711c78d8798158349d12fa8ca513efb14");
+
+        let mut writer = FiniteDataWriter::new("abc", Some(100));
+        writer.pull();
+        assert_eq!(writer.remaining(), Some(36));
+        assert_eq!(writer.into_vec().unwrap().len(), 36);
+
+        let writer = FiniteDataWriter::new("abc", None);
+        assert_eq!(writer.remaining(), None);
+        assert!(matches!(writer.into_vec(), Err(Error::UnboundedLength)));
+    }
+
+    #[test]
+    fn write_hex_to_formatter() {
+        let expected = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd400992366cb547665e462bbdd51d9b6ce1221116e9cfc6711c78d8798158349d12fa8ca513efb14";
+        let mut hex = String::new();
+        FiniteDataWriter::new("abc", Some(100))
+            .write_hex_to(&mut hex, 1000)
+            .unwrap();
+        assert_eq!(hex, expected);
+
+        let mut writer = FiniteDataWriter::new("abc", None);
+        let mut hex = String::new();
+        writer.write_hex_to(&mut hex, 10).unwrap();
+        assert_eq!(hex, expected[..20]);
+        writer.write_hex_to(&mut hex, 90).unwrap();
+        assert_eq!(hex, expected);
+    }
+
+    #[test]
+    fn write_hex_to_is_contiguous() {
+        for (a, b) in [
+            (0, 100),
+            (1, 1),
+            (10, 90),
+            (63, 2),
+            (64, 64),
+            (65, 200),
+            (150, 10),
+        ] {
+            let mut whole = String::new();
+            FiniteDataWriter::new("abc", Some(300))
+                .write_hex_to(&mut whole, a + b)
+                .unwrap();
+
+            let mut writer = FiniteDataWriter::new("abc", Some(300));
+            let mut split = String::new();
+            writer.write_hex_to(&mut split, a).unwrap();
+            writer.write_hex_to(&mut split, b).unwrap();
+            assert_eq!(split, whole);
+
+            // Pulls continue after the written bytes too
+            let expected = FiniteDataWriter::new("abc", Some(300)).into_vec().unwrap();
+            let rest = writer.into_vec().unwrap();
+            assert_eq!(rest, expected[a + b..]);
+        }
+    }
+
+    #[test]
+    fn exhausted_at_desired_length() {
+        let mut writer = FiniteDataWriter::new("abc", Some(100));
+        assert!(!writer.is_exhausted());
+        assert_eq!(writer.pull().len(), 64);
+        assert!(!writer.is_exhausted());
+        assert_eq!(writer.pull().len(), 36);
+        assert!(writer.is_exhausted());
+        assert!(writer.pull().is_empty());
+
+        assert!(FiniteDataWriter::new("abc", Some(0)).is_exhausted());
+        let mut writer = FiniteDataWriter::new("abc", Some(128));
+        writer.pull();
+        writer.pull();
+        assert!(writer.is_exhausted());
+
+        let mut writer = FiniteDataWriter::new("abc", None);
+        for _ in 0..10 {
+            writer.pull();
+            assert!(!writer.is_exhausted());
+        }
+    }
+
+    #[test]
+    fn hash_evaluations_per_length() {
+        assert_eq!(hash_evaluations(0), 0);
+        assert_eq!(hash_evaluations(100), 2);
+        assert_eq!(hash_evaluations(128), 2);
+        assert_eq!(hash_evaluations(129), 3);
+    }
+
+    #[test]
+    fn pull_into_matches_pull() {
+        let mut expected = InfiniteDataWriter::new("abc");
+        let mut writer = InfiniteDataWriter::new("abc");
+        let mut batch = [0; 64];
+        for _ in 0..10 {
+            writer.pull_into(&mut batch);
+            assert_eq!(batch, expected.pull());
         }
     }
 
@@ -192,7 +559,7 @@ mod tests {
         const SEED: &str = "abc";
 
         // Generate data with MAX_SIZE limit.
-        let writer = FiniteDataWriter::new(SEED, Some(MAX_SIZE));
+        let writer = FiniteDataWriter::new(SEED, Some(MAX_SIZE as u64));
         let expected = writer.into_iter().fold(Vec::new(), |mut so_far, curr| {
             so_far.extend(curr);
             so_far
@@ -202,7 +569,7 @@ mod tests {
 
         // Make sure that any data generated with size < MAX_SIZE is a subset of the previous result.
         for curr_size in 0..MAX_SIZE {
-            let writer = FiniteDataWriter::new(SEED, Some(curr_size));
+            let writer = FiniteDataWriter::new(SEED, Some(curr_size as u64));
             let actual = writer.into_iter().fold(Vec::new(), |mut so_far, curr| {
                 so_far.extend(curr);
                 so_far
@@ -211,4 +578,129 @@ mod tests {
             assert_eq!(actual, expected[0..curr_size]);
         }
     }
+
+    proptest! {
+        #[test]
+        fn random_sizes_homomorphism(
+            seed in prop::collection::vec(any::<u8>(), 0..200),
+            length in 0..5000u64,
+            extra in 0..300u64,
+        ) {
+            let shorter = generate(&seed, length).unwrap();
+            let longer = generate(&seed, length + extra).unwrap();
+            prop_assert_eq!(shorter.len() as u64, length);
+            prop_assert_eq!(&shorter[..], &longer[..length as usize]);
+        }
+    }
+
+    #[test]
+    fn boxed_iter_matches_concrete() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let boxed = boxed_iter("abc", Some(1000));
+        assert_send(&boxed);
+        assert_eq!(
+            boxed.collect::<Vec<_>>(),
+            FiniteDataWriter::new("abc", Some(1000)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mix_in_forks_stream() {
+        let branch = |extra: Option<&[u8]>| {
+            let mut writer = InfiniteDataWriter::new("abc");
+            writer.pull();
+            if let Some(extra) = extra {
+                writer.mix_in(extra);
+            }
+            writer.take(3).collect::<Vec<_>>()
+        };
+
+        assert_eq!(branch(Some(b"x")), branch(Some(b"x")));
+        assert_ne!(branch(Some(b"x")), branch(None));
+        assert_ne!(branch(Some(b"x")), branch(Some(b"y")));
+        // Mixing in nothing still hashes the state
+        assert_ne!(branch(Some(b"")), branch(None));
+    }
+
+    #[test]
+    fn finite_pull_into_matches_pull() {
+        let mut writer = FiniteDataWriter::new("abc", Some(100));
+        let mut batch = [0; 64];
+        let mut data = Vec::new();
+        loop {
+            let length = writer.pull_into(&mut batch);
+            if length == 0 {
+                break;
+            }
+            data.extend_from_slice(&batch[..length]);
+        }
+        assert_eq!(
+            data,
+            FiniteDataWriter::new("abc", Some(100))
+                .flatten()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn finite_from_seed_bytes() {
+        let seed = *InfiniteDataWriter::new("abc").seed();
+        assert_eq!(
+            FiniteDataWriter::from_seed_bytes(seed, Some(100)).collect::<Vec<_>>(),
+            FiniteDataWriter::new("abc", Some(100)).collect::<Vec<_>>()
+        );
+    }
+
+    /// Discards the bytes written to it, and counts them
+    struct CountingSink(u64);
+
+    impl std::io::Write for CountingSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn length_beyond_u32() {
+        use std::io::Write;
+
+        let desired_length = u32::MAX as u64 + 100;
+        let start = u32::MAX as u64 - 28;
+
+        // Start close to the end of the stream, as if the data before it had been written to the sink already
+        let mut writer = FiniteDataWriter::new("abc", Some(desired_length));
+        writer.pulled_length = start;
+        let mut sink = CountingSink(start);
+        let mut batch = [0; 64];
+        loop {
+            let length = writer.pull_into(&mut batch);
+            if length == 0 {
+                break;
+            }
+            sink.write_all(&batch[..length]).unwrap();
+        }
+        assert_eq!(sink.0, desired_length);
+        assert!(writer.is_exhausted());
+        assert_eq!(writer.remaining(), Some(0));
+    }
+
+    #[test]
+    fn pull_ref_matches_pull() {
+        let mut by_ref = InfiniteDataWriter::new("abc");
+        let mut by_value = InfiniteDataWriter::new("abc");
+        for _ in 0..10 {
+            let batch = *by_ref.pull_ref();
+            assert_eq!(batch, by_value.pull());
+            assert_eq!(by_ref.seed(), by_value.seed());
+        }
+
+        // Mixing the ways of pulling keeps the stream
+        assert_eq!(by_ref.pull(), *by_value.pull_ref());
+    }
 }
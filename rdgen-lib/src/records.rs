@@ -0,0 +1,36 @@
+use crate::{byte_stream::ByteStream, InfiniteDataWriter};
+
+/// Iterate over `count` records of `record_size` bytes each, where every record is the next bytes
+/// of the stream of the given seed. Put together, the records are `generate(seed, record_size * count)`,
+/// but they are generated as they are consumed, without holding the whole stream in memory.
+pub fn records(
+    seed: impl AsRef<[u8]>,
+    record_size: usize,
+    count: u64,
+) -> impl Iterator<Item = Vec<u8>> {
+    let mut stream = ByteStream::new(InfiniteDataWriter::new(seed));
+    (0..count).map(move |_| {
+        let mut record = vec![0; record_size];
+        stream.fill(&mut record);
+        record
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn records_are_the_stream() {
+        for (record_size, count) in [(1, 100), (10, 13), (64, 4), (100, 7), (3, 0)] {
+            let records: Vec<Vec<u8>> = records("abc", record_size, count).collect();
+            assert_eq!(records.len() as u64, count);
+            assert!(records.iter().all(|r| r.len() == record_size));
+            assert_eq!(
+                records.concat(),
+                generate("abc", record_size as u64 * count).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use rand_core::{RngCore, SeedableRng};
+
+use crate::{byte_stream::ByteStream, InfiniteDataWriter};
+
+/// An adapter that implements `rand_core::RngCore` over the stream of an `InfiniteDataWriter`,
+/// so that the familiar APIs of the `rand` ecosystem produce reproducible results.
+///
+/// `fill_bytes` fills the buffer with the next bytes of the stream, and `next_u32`/`next_u64` consume
+/// the next 4/8 bytes of the stream as a little-endian number.
+#[must_use]
+pub struct DataWriterRng {
+    stream: ByteStream,
+}
+
+impl DataWriterRng {
+    pub fn new(writer: InfiniteDataWriter) -> Self {
+        Self {
+            stream: ByteStream::new(writer),
+        }
+    }
+}
+
+impl RngCore for DataWriterRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buffer = [0; 4];
+        self.stream.fill(&mut buffer);
+        u32::from_le_bytes(buffer)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buffer = [0; 8];
+        self.stream.fill(&mut buffer);
+        u64::from_le_bytes(buffer)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.stream.fill(dst);
+    }
+}
+
+impl SeedableRng for DataWriterRng {
+    /// The seed is hashed like any other seed, so `from_seed(seed)` produces the stream of `InfiniteDataWriter::new(seed)`
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(InfiniteDataWriter::new(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_bytes_matches_stream() {
+        let expected: Vec<u8> = InfiniteDataWriter::new("abc")
+            .flatten()
+            .take(1000)
+            .collect();
+
+        let mut rng = DataWriterRng::new(InfiniteDataWriter::new("abc"));
+        let mut actual = vec![0; 1000];
+        actual
+            .chunks_mut(37)
+            .for_each(|chunk| rng.fill_bytes(chunk));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn numbers_consume_stream() {
+        let expected: Vec<u8> = InfiniteDataWriter::new("abc").flatten().take(12).collect();
+
+        let mut rng = DataWriterRng::new(InfiniteDataWriter::new("abc"));
+        assert_eq!(
+            rng.next_u32(),
+            u32::from_le_bytes(expected[..4].try_into().unwrap())
+        );
+        assert_eq!(
+            rng.next_u64(),
+            u64::from_le_bytes(expected[4..12].try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn from_seed_hashes_seed() {
+        let mut rng = DataWriterRng::from_seed([1; 32]);
+        let mut expected = InfiniteDataWriter::new([1; 32]);
+
+        let mut actual = [0; 64];
+        rng.fill_bytes(&mut actual);
+        assert_eq!(actual, expected.pull());
+    }
+}
@@ -0,0 +1,35 @@
+//! Helpers for the test suites of crates that use rdgen.
+
+use crate::FiniteDataWriter;
+
+/// Generate `length` bytes from the given seed twice, assert that both runs produce the same data,
+/// and return the data.
+pub fn assert_reproducible(seed: impl AsRef<[u8]>, length: usize) -> Vec<u8> {
+    let generate = || {
+        FiniteDataWriter::new(seed.as_ref(), Some(length as u64)).fold(
+            Vec::with_capacity(length),
+            |mut so_far, curr| {
+                so_far.extend(curr);
+                so_far
+            },
+        )
+    };
+
+    let first = generate();
+    let second = generate();
+    assert_eq!(first.len(), length, "Generated data has the wrong length");
+    assert_eq!(first, second, "Generated data is not reproducible");
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let data = assert_reproducible("abc", 100);
+        assert_eq!(hex::encode(data), "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd400992366cb547665e462bbdd51d9b6ce1221116e9cfc6711c78d8798158349d12fa8ca513efb14");
+        assert!(assert_reproducible("abc", 0).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use std::{io::Cursor, num::NonZeroUsize};
+
+use blake2::{
+    digest::{Update, VariableOutput},
+    Blake2bVar,
+};
+
+use crate::{digest_writer::update_from_stream, Error};
+
+/// The largest output size of Blake2b, which is the batch size of `InfiniteDataWriter`
+const MAX_DIGEST_SIZE: usize = 64;
+
+/// The hash chain of `InfiniteDataWriter`, with Blake2b of the given output size, from 1 to 64 bytes,
+/// so every batch has that size. Blake2b commits to its output size, so a smaller output is a different
+/// hash, not a truncation of the 64-byte one, and every digest size produces a completely different stream.
+/// With 64 bytes, the stream is the one of `InfiniteDataWriter`.
+#[derive(Clone)]
+#[must_use]
+pub struct VariableDataWriter {
+    seed: Vec<u8>,
+}
+
+impl VariableDataWriter {
+    /// Create a new instance with the given seed, and a digest size from 1 to 64 bytes.
+    pub fn new(seed: impl AsRef<[u8]>, digest_size: usize) -> Result<Self, Error> {
+        Self::new_from_stream(Cursor::new(seed.as_ref()), digest_size)
+    }
+
+    /// Create a new instance with the given stream of data, and a digest size from 1 to 64 bytes.
+    pub fn new_from_stream(source: impl std::io::Read, digest_size: usize) -> Result<Self, Error> {
+        let mut hasher = hasher(digest_size)?;
+        update_from_stream(source, |data| hasher.update(data))?;
+        let mut seed = vec![0; digest_size];
+        hasher
+            .finalize_variable(&mut seed)
+            .expect("The seed has the digest size");
+        Ok(Self { seed })
+    }
+
+    /// Pull a batch of data, and generate new data in seed
+    pub fn pull(&mut self) -> Vec<u8> {
+        let mut hasher = hasher(self.seed.len()).expect("The digest size was checked");
+        hasher.update(&self.seed);
+        let mut seed = vec![0; self.seed.len()];
+        hasher
+            .finalize_variable(&mut seed)
+            .expect("The seed has the digest size");
+        std::mem::swap(&mut seed, &mut self.seed);
+        seed
+    }
+
+    /// Fork the stream deterministically by updating the internal seed to the hash of `seed || extra`,
+    /// like `InfiniteDataWriter::mix_in`, with the digest size of this writer.
+    pub fn mix_in(&mut self, extra: &[u8]) {
+        let mut hasher = hasher(self.seed.len()).expect("The digest size was checked");
+        hasher.update(&self.seed);
+        hasher.update(extra);
+        hasher
+            .finalize_variable(&mut self.seed)
+            .expect("The seed has the digest size");
+    }
+
+    /// The size of every batch, which is the digest size
+    pub fn batch_size(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.seed.len()).expect("Size must be larger than zero")
+    }
+}
+
+fn hasher(digest_size: usize) -> Result<Blake2bVar, Error> {
+    if !(1..=MAX_DIGEST_SIZE).contains(&digest_size) {
+        return Err(Error::InvalidDigestSize(digest_size));
+    }
+    Ok(Blake2bVar::new(digest_size).expect("The size is in the range of Blake2b"))
+}
+
+impl Iterator for VariableDataWriter {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.pull())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::InfiniteDataWriter;
+
+    fn stream(digest_size: usize, length: usize) -> String {
+        let writer = VariableDataWriter::new("abc", digest_size).unwrap();
+        hex::encode(writer.flatten().take(length).collect::<Vec<u8>>())
+    }
+
+    #[test]
+    fn vectors_per_digest_size() {
+        assert_eq!(stream(32, 100), "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319f6674caaed0b83eae0ae556b6c7d45a12463413a4822152989a0c78c9428f4f3fa82480dacf3e3280e4486a6721cc9a8eb1fbeed35762c597c7d06fa9a33e29e4fdc6309");
+        assert_eq!(stream(16, 100), "cf4ab791c62b8d2b2109c9027528781609733665c3ed003fa4cf362839724be969dca99e08778116373de75fbedff161b68327451955b175ae83873effbef80e3e6a2b96e5d7ac34c1aaf86bd8d82d667af34aaccd376e555d67946d2d7bd4f9e80989ca");
+    }
+
+    #[test]
+    fn full_size_matches_infinite_writer() {
+        let mut expected = InfiniteDataWriter::new("abc");
+        let mut writer = VariableDataWriter::new("abc", 64).unwrap();
+        assert_eq!(writer.batch_size().get(), 64);
+        for _ in 0..10 {
+            assert_eq!(writer.pull(), expected.pull());
+        }
+    }
+
+    #[test]
+    fn full_size_mix_in_matches_infinite_writer() {
+        let mut expected = InfiniteDataWriter::new("abc");
+        expected.mix_in(b"x");
+        let mut writer = VariableDataWriter::new("abc", 64).unwrap();
+        writer.mix_in(b"x");
+        assert_eq!(writer.pull(), expected.pull());
+
+        let mut other = VariableDataWriter::new("abc", 32).unwrap();
+        let unmixed = other.clone().pull();
+        other.mix_in(b"x");
+        assert_ne!(other.pull(), unmixed);
+    }
+
+    #[test]
+    fn batches_have_digest_size() {
+        for digest_size in [1, 16, 63] {
+            let mut writer = VariableDataWriter::new("abc", digest_size).unwrap();
+            assert_eq!(writer.batch_size().get(), digest_size);
+            assert_eq!(writer.pull().len(), digest_size);
+        }
+        assert!(VariableDataWriter::new("abc", 0).is_err());
+        assert!(VariableDataWriter::new("abc", 65).is_err());
+    }
+}
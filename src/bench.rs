@@ -0,0 +1,125 @@
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use rdgen_lib::{CounterDataWriter, FiniteDataWriter};
+
+use crate::stream::{Chunks, DataSource, GENERATION_BUFFER_SIZE};
+
+/// The seed of the benchmark, since the speed of generation does not depend on the seed
+const BENCH_SEED: &[u8] = b"rdgen bench";
+
+/// The result of generating data with one of the generators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchResult {
+    pub name: &'static str,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    pub fn mib_per_second(&self) -> f64 {
+        self.bytes as f64 / (1024.0 * 1024.0) / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// The generators of this build, by the values of --generator and --hash, with a source of `length` bytes
+fn generators(length: u64) -> Vec<(&'static str, Box<dyn DataSource>)> {
+    let mut generators: Vec<(&'static str, Box<dyn DataSource>)> = vec![
+        (
+            "chain",
+            Box::new(FiniteDataWriter::new(BENCH_SEED, Some(length))),
+        ),
+        (
+            "counter",
+            Box::new(CounterDataWriter::new(BENCH_SEED, Some(length))),
+        ),
+    ];
+    #[cfg(feature = "sha256")]
+    generators.push((
+        "chain-sha256",
+        Box::new(Chunks::new(crate::stream::take_bytes(
+            rdgen_lib::Sha256DataWriter::new(BENCH_SEED).map(|batch| batch.to_vec()),
+            length,
+        ))),
+    ));
+    generators.push((
+        "chain-digest-size-32",
+        Box::new(Chunks::new(crate::stream::take_bytes(
+            rdgen_lib::VariableDataWriter::new(BENCH_SEED, 32).expect("The size is valid"),
+            length,
+        ))),
+    ));
+    generators
+}
+
+/// Generate `length` bytes with every generator of this build, without writing them anywhere,
+/// and return how long every generator took.
+pub fn run_bench(length: u64) -> Vec<BenchResult> {
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+    generators(length)
+        .into_iter()
+        .map(|(name, mut source)| {
+            let start = Instant::now();
+            let mut bytes = 0;
+            loop {
+                let filled = source.fill(&mut buffer);
+                if filled == 0 {
+                    break;
+                }
+                bytes += filled as u64;
+            }
+            BenchResult {
+                name,
+                bytes,
+                elapsed: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+/// Write the results as a table, with a header line and a line per generator, where the columns are
+/// separated by whitespace: the generator, the number of bytes, the seconds, and the throughput in MiB/s.
+pub fn write_report(results: &[BenchResult], mut out: impl Write) -> std::io::Result<()> {
+    writeln!(out, "generator bytes seconds mib_per_second")?;
+    for result in results {
+        writeln!(
+            out,
+            "{} {} {:.6} {:.1}",
+            result.name,
+            result.bytes,
+            result.elapsed.as_secs_f64(),
+            result.mib_per_second()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_is_parseable() {
+        let results = run_bench(100_000);
+        let mut report = Vec::new();
+        write_report(&results, &mut report).unwrap();
+
+        let report = String::from_utf8(report).unwrap();
+        let mut lines = report.lines();
+        assert_eq!(lines.next(), Some("generator bytes seconds mib_per_second"));
+        let names: Vec<&str> = lines
+            .map(|line| {
+                let columns: Vec<&str> = line.split_whitespace().collect();
+                assert_eq!(columns.len(), 4, "{line}");
+                assert_eq!(columns[1].parse::<u64>().unwrap(), 100_000);
+                assert!(columns[2].parse::<f64>().unwrap() >= 0.0);
+                assert!(columns[3].parse::<f64>().unwrap() >= 0.0);
+                columns[0]
+            })
+            .collect();
+        assert_eq!(names.len(), results.len());
+        assert!(names.contains(&"chain") && names.contains(&"counter"));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use blake2::{Blake2b512, Digest};
+
+const BATCH_SIZE: usize = 64;
+
+/// The state of a run of the hash chain at a batch boundary of the output, from which the rest of the output
+/// can be generated with `InfiniteDataWriter::from_seed_bytes(state)`.
+///
+/// In its text format, every line has a key and a value, separated by a space: `offset` and `length`
+/// in bytes, and `state` in hex. Empty lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The number of bytes of the output written when the checkpoint was taken
+    pub offset: u64,
+    /// The length of the whole output
+    pub length: u64,
+    /// The internal state of the hash chain at the offset
+    pub state: [u8; 64],
+}
+
+impl Checkpoint {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let (mut offset, mut length, mut state) = (None, None, None);
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                |reason: &str| anyhow::anyhow!("Invalid line {} of checkpoint: {reason}", i + 1);
+
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| invalid("expected a key and a value"))?;
+            match key {
+                "offset" => {
+                    let value: u64 = value.parse().map_err(|_| invalid("invalid offset"))?;
+                    // Checkpoints are only taken at batch boundaries, where the state is known
+                    if value % BATCH_SIZE as u64 != 0 {
+                        return Err(invalid("the offset is not a multiple of 64"));
+                    }
+                    offset = Some(value);
+                }
+                "length" => length = Some(value.parse().map_err(|_| invalid("invalid length"))?),
+                "state" => {
+                    let mut bytes = [0; 64];
+                    hex::decode_to_slice(value, &mut bytes)
+                        .map_err(|_| invalid("invalid state"))?;
+                    state = Some(bytes);
+                }
+                _ => return Err(invalid("unknown key")),
+            }
+        }
+
+        match (offset, length, state) {
+            (Some(offset), Some(length), Some(state)) if offset <= length => Ok(Self {
+                offset,
+                length,
+                state,
+            }),
+            (Some(_), Some(_), Some(_)) => Err(anyhow::anyhow!(
+                "The offset of the checkpoint is beyond its length"
+            )),
+            _ => Err(anyhow::anyhow!(
+                "The checkpoint must have an offset, a length and a state"
+            )),
+        }
+    }
+
+    pub fn read(p: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(p)
+            .context(format!("Reading checkpoint failed: {}", p.display()))?;
+        Self::parse(&text).context(format!("Parsing checkpoint failed: {}", p.display()))
+    }
+
+    /// Write the checkpoint to a temporary file next to the given path, and then rename it, so that
+    /// the file at the path is always a complete checkpoint, even if the program stops while writing it.
+    pub fn write(&self, p: &Path) -> std::io::Result<()> {
+        let mut temporary = p.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, self.to_string())?;
+        std::fs::rename(&temporary, p)
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# rdgen checkpoint")?;
+        writeln!(f, "offset {}", self.offset)?;
+        writeln!(f, "length {}", self.length)?;
+        writeln!(f, "state {}", hex::encode(self.state))
+    }
+}
+
+/// A writer of the output of the hash chain that writes a checkpoint every `every` bytes, which must be
+/// a multiple of the batch size. Before a checkpoint is written, the output is flushed, so that the output
+/// has at least the bytes up to the offset of the checkpoint.
+///
+/// Since every batch of the hash chain is its internal state, the state at a batch boundary is the hash
+/// of the batch before it, so the checkpoints are taken from the data written, without access to the generator.
+pub struct Checkpointing<W: Write> {
+    inner: W,
+    path: PathBuf,
+    every: u64,
+    length: u64,
+    offset: u64,
+    last_batch: [u8; BATCH_SIZE],
+}
+
+impl<W: Write> Checkpointing<W> {
+    /// Start taking checkpoints of an output of `length` bytes, where the data written starts at `offset`,
+    /// which must be a batch boundary.
+    pub fn new(inner: W, path: PathBuf, every: u64, offset: u64, length: u64) -> Self {
+        assert!(every > 0 && every % BATCH_SIZE as u64 == 0);
+        assert!(offset % BATCH_SIZE as u64 == 0);
+        Self {
+            inner,
+            path,
+            every,
+            length,
+            offset,
+            last_batch: [0; BATCH_SIZE],
+        }
+    }
+
+    fn write_checkpoint(&mut self) -> std::io::Result<()> {
+        self.inner.flush()?;
+        let checkpoint = Checkpoint {
+            offset: self.offset,
+            length: self.length,
+            state: Blake2b512::digest(self.last_batch).into(),
+        };
+        checkpoint.write(&self.path)
+    }
+}
+
+impl<W: Write> Write for Checkpointing<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Only the bytes up to the next checkpoint can be written now
+        let next_checkpoint = (self.offset / self.every + 1) * self.every;
+        let max_length = (next_checkpoint - self.offset).min(buf.len() as u64) as usize;
+        let written = self.inner.write(&buf[..max_length])?;
+
+        for &byte in &buf[..written] {
+            self.last_batch[(self.offset % BATCH_SIZE as u64) as usize] = byte;
+            self.offset += 1;
+        }
+        if self.offset == next_checkpoint {
+            self.write_checkpoint()?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::{FiniteDataWriter, InfiniteDataWriter};
+
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let checkpoint = Checkpoint {
+            offset: 128,
+            length: 1000,
+            state: *InfiniteDataWriter::new("abc").seed(),
+        };
+        assert_eq!(
+            Checkpoint::parse(&checkpoint.to_string()).unwrap(),
+            checkpoint
+        );
+
+        let state = "00".repeat(64);
+        for invalid in [
+            String::new(),
+            "offset 64\nlength 128".to_string(),
+            "offset 64\nlength 128\nstate 00".to_string(),
+            format!("offset a\nlength 2\nstate {state}"),
+            format!("offset 128\nlength 64\nstate {state}"),
+            format!("offset 100\nlength 200\nstate {state}"),
+            format!("offset 64\nlength 128\nstate {state}\nother 1"),
+        ] {
+            assert!(Checkpoint::parse(&invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn checkpoint_resumes_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint");
+        let data: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+
+        let mut out = Vec::new();
+        let mut writer = Checkpointing::new(&mut out, path.clone(), 256, 0, 1000);
+        writer.write_all(&data[..600]).unwrap();
+
+        let checkpoint = Checkpoint::read(&path).unwrap();
+        assert_eq!(checkpoint.offset, 512);
+        assert_eq!(checkpoint.length, 1000);
+        let resumed: Vec<u8> = FiniteDataWriter::from_seed_bytes(checkpoint.state, Some(488))
+            .flatten()
+            .collect();
+        assert_eq!(resumed, data[512..]);
+    }
+}
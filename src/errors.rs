@@ -0,0 +1,82 @@
+//! The categories of failures of the program, which determine its exit code, so that scripts can tell them apart.
+//! Errors are tagged with their category where they happen, and the tag survives any context added later.
+
+use crate::exit_codes;
+
+#[derive(Debug)]
+pub enum CliError {
+    /// The seed could not be read, or is not valid
+    SeedSource(anyhow::Error),
+    /// The output could not be opened or written
+    Output(anyhow::Error),
+    /// The options given are not valid together
+    InvalidOptions(String),
+}
+
+impl CliError {
+    pub fn seed_source(e: anyhow::Error) -> anyhow::Error {
+        CliError::SeedSource(e).into()
+    }
+
+    pub fn output(e: anyhow::Error) -> anyhow::Error {
+        CliError::Output(e).into()
+    }
+
+    pub fn invalid_options(message: impl Into<String>) -> anyhow::Error {
+        CliError::InvalidOptions(message.into()).into()
+    }
+
+    pub const fn exit_code(&self) -> u8 {
+        match self {
+            CliError::SeedSource(_) => exit_codes::SEED_SOURCE,
+            CliError::Output(_) => exit_codes::OUTPUT,
+            CliError::InvalidOptions(_) => exit_codes::INVALID_OPTIONS,
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::SeedSource(e) | CliError::Output(e) => e.fmt(f),
+            CliError::InvalidOptions(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::SeedSource(e) | CliError::Output(e) => e.source(),
+            CliError::InvalidOptions(_) => None,
+        }
+    }
+}
+
+/// The exit code for the given error, by its category, or `FAILURE` if it has none.
+/// An output interrupted by a signal has the conventional exit code of the signal, whatever its category.
+pub fn exit_code(e: &anyhow::Error) -> u8 {
+    #[cfg(all(unix, feature = "signals"))]
+    if let Some(interrupted) = crate::output::interruption(e) {
+        return interrupted.exit_code();
+    }
+    e.downcast_ref::<CliError>()
+        .map_or(exit_codes::FAILURE, CliError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn category_survives_context() {
+        let e = CliError::output(anyhow::anyhow!("Disk full"));
+        let e = Err::<(), _>(e).context("Writing failed").unwrap_err();
+        assert_eq!(exit_code(&e), exit_codes::OUTPUT);
+        assert_eq!(format!("{:#}", e), "Writing failed: Disk full");
+
+        assert_eq!(exit_code(&anyhow::anyhow!("Other")), exit_codes::FAILURE);
+    }
+}
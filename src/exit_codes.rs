@@ -0,0 +1,18 @@
+//! The exit codes of the program, besides the ones used by the options parser on usage errors (2).
+
+pub const SUCCESS: u8 = 0;
+
+/// A failure that does not fall in any of the categories below
+pub const FAILURE: u8 = 1;
+
+/// The generated data does not match what it was checked against
+pub const MISMATCH: u8 = 3;
+
+/// The seed could not be read, or is not valid
+pub const SEED_SOURCE: u8 = 4;
+
+/// The output could not be opened or written
+pub const OUTPUT: u8 = 5;
+
+/// The options given are not valid together
+pub const INVALID_OPTIONS: u8 = 6;
@@ -1,40 +1,1605 @@
-use std::io::{Read, Write};
+use std::{
+    io::{BufReader, IsTerminal, Read, Write},
+    process::ExitCode,
+};
 
 use anyhow::Context;
 use clap::Parser;
-use rdgen_lib::FiniteDataWriter;
+use errors::CliError;
+use modes::compare_stream::StreamComparison;
+use program_options::{Format, Generator, HashFunction};
+use rdgen_lib::{CounterDataWriter, FiniteDataWriter, InfiniteDataWriter};
+use stream::{Chunks, DataSource};
 
+mod bench;
+mod checkpoint;
+mod errors;
+mod exit_codes;
+mod metadata;
+mod modes;
+mod observers;
+mod output;
 mod program_options;
+mod self_test;
+mod stats;
+mod stream;
 
-fn main() -> anyhow::Result<()> {
-    let args: program_options::RDGenOptions = program_options::RDGenOptions::parse();
+fn main() -> ExitCode {
+    let args = program_options::RDGenOptions::parse();
+    // Installed here rather than in `run`, so that the tests that call `run` keep the default handlers
+    #[cfg(all(unix, feature = "signals"))]
+    let result = install_signal_handlers(&args).and_then(|()| run(args));
+    #[cfg(not(all(unix, feature = "signals")))]
+    let result = run(args);
 
-    let data_writer = match args.file {
-        Some(f) => {
-            let reader = open_file(f)?;
-            FiniteDataWriter::new_from_stream(reader, Some(args.length))?
+    match result {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(errors::exit_code(&e))
         }
-        None => {
-            let stdin = std::io::stdin();
-            FiniteDataWriter::new_from_stream(stdin, Some(args.length))?
+    }
+}
+
+/// Install the handlers of the signals that stop the output cleanly, and of the ones of --pause-signals
+#[cfg(all(unix, feature = "signals"))]
+fn install_signal_handlers(args: &program_options::RDGenOptions) -> anyhow::Result<()> {
+    output::install_interrupt_signals().context("Installing signal handlers failed")?;
+    if args.pause_signals {
+        output::install_pause_signals().context("Installing signal handlers failed")?;
+    }
+    Ok(())
+}
+
+fn run(args: program_options::RDGenOptions) -> anyhow::Result<ExitCode> {
+    if let Some(nonce) = &args.nonce {
+        eprintln!("Nonce: {}", hex::encode(&nonce.0));
+    }
+    let seed = RunSeed::new(&args);
+    let exit_code = run_mode(&args, &seed)?;
+    if let Some(p) = &args.metadata {
+        if exit_code == ExitCode::SUCCESS {
+            write_metadata(&args, &seed, p)?;
+        }
+    }
+    Ok(exit_code)
+}
+
+fn run_mode(args: &program_options::RDGenOptions, seed: &RunSeed) -> anyhow::Result<ExitCode> {
+    if args.self_test {
+        let count = self_test::run_self_test()?;
+        println!("Self-test passed: {count} reference vectors reproduced");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.bench {
+        let results = bench::run_bench(args.bench_size);
+        bench::write_report(&results, std::io::stderr().lock())
+            .context("Writing benchmark report failed")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let (Some(output), Some(seed)) = (&args.if_newer, &args.file) {
+        if is_newer_than(output, seed)? {
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+
+    if let Some(count) = args.dump_states {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        modes::dump_states::write_states(writer, count, std::io::stderr().lock())
+            .context("Writing internal states to stderr failed")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(n) = args.derive_seeds {
+        let children = rdgen_lib::derive_seeds(seed.bytes()?, n);
+        return write_output(args, |out| {
+            let mut out = text_output(out, args);
+            children
+                .iter()
+                .try_for_each(|child| writeln!(out, "{}", hex::encode(child)))
+                .context("Writing derived seeds to output failed")
+        });
+    }
+
+    if let Some(p) = &args.compare_hashes {
+        let data = open_data(args, p)?;
+        // The stream of the generator of the options, as long as the data can be
+        let generated = data_source(args, seed, 0, u64::MAX)?;
+        let differing = modes::compare_hashes::differing_windows(generated, data, args.window)
+            .context(format!("Reading file failed: {}", p.display()))?;
+        for index in &differing {
+            println!("Window {index} differs, at offset {}", index * args.window);
+        }
+        return Ok(ExitCode::from(if differing.is_empty() {
+            exit_codes::SUCCESS
+        } else {
+            exit_codes::MISMATCH
+        }));
+    }
+
+    if let Some(dir) = &args.compare_dir {
+        let files = modes::compare_dir::split_files(dir)
+            .context(format!("Reading directory failed: {}", dir.display()))?;
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        let split_size = args.split_size.expect("Required by the options");
+        let checks = modes::compare_dir::compare_dir(writer, &files, split_size, args.length)
+            .context(format!("Reading files failed: {}", dir.display()))?;
+        for (index, (path, check)) in checks.iter().enumerate() {
+            match path {
+                Some(path) => println!("{}: {check}", path.display()),
+                None => println!("File {index}: {check}"),
+            }
+        }
+        return Ok(ExitCode::from(if checks.iter().all(|(_, c)| c.passed()) {
+            exit_codes::SUCCESS
+        } else {
+            exit_codes::MISMATCH
+        }));
+    }
+
+    #[cfg(feature = "listen")]
+    if let Some(address) = &args.listen {
+        let internal_seed = seed.internal()?;
+        let listener = std::net::TcpListener::bind(address)
+            .context(format!("Listening failed on: {address}"))?;
+        eprintln!("Listening on {}", listener.local_addr()?);
+        modes::listen::serve(listener, internal_seed, args.length)
+            .context("Accepting connections failed")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(p) = &args.prefix_match {
+        let data = open_data(args, p)?;
+        // The stream of the generator of the options, as long as the data can be
+        let generated = data_source(args, seed, 0, u64::MAX)?;
+        let matching = modes::prefix_match::matching_prefix_length(generated, data)
+            .context(format!("Reading file failed: {}", p.display()))?;
+        println!("{matching}");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(count) = args.kv_lines {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::kv_lines::write_kv_lines(
+                writer,
+                count,
+                args.key_length,
+                args.value_length,
+                &mut text_output(out, args),
+            )
+            .context("Writing key-value lines to output failed")
+        });
+    }
+
+    if let Some(count) = args.log_lines {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::log_lines::write_log_lines(
+                writer,
+                count,
+                args.line_length,
+                &mut text_output(out, args),
+            )
+            .context("Writing log lines to output failed")
+        });
+    }
+
+    if let Some(count) = args.numbered_lines {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::numbered_lines::write_numbered_lines(
+                writer,
+                count,
+                args.line_length,
+                &mut text_output(out, args),
+            )
+            .context("Writing numbered lines to output failed")
+        });
+    }
+
+    if let Some(p) = &args.choices_file {
+        let choices = modes::choices::read_choices(open_file(p)?)
+            .context(format!("Reading choices failed: {}", p.display()))?;
+        if choices.is_empty() {
+            return Err(anyhow::anyhow!("No choices found in: {}", p.display()));
+        }
+        let count = args
+            .choices_count
+            .expect("Choices count is required by the options parser");
+
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::choices::write_choices(writer, &choices, count, &mut text_output(out, args))
+                .context("Writing choices to output failed")
+        });
+    }
+
+    if let Some(count) = args.corrupt {
+        let p = args
+            .corrupt_input
+            .as_ref()
+            .expect("Input is required by the options parser with --corrupt");
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        let mut data = Vec::new();
+        open_file(p)?
+            .read_to_end(&mut data)
+            .context(format!("Reading file failed: {}", p.display()))?;
+
+        modes::corrupt::corrupt(writer, &mut data, count)
+            .map_err(|e| CliError::invalid_options(e.to_string()))?;
+        return write_output(args, |out| {
+            out.write_all(&data)
+                .context("Writing corrupted data to output failed")
+        });
+    }
+
+    if let Some(p) = &args.shuffle_lines {
+        let lines = modes::shuffle_lines::read_lines(open_file(p)?)
+            .context(format!("Reading lines failed: {}", p.display()))?;
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::shuffle_lines::write_shuffled_lines(writer, lines, out)
+                .context("Writing shuffled lines to output failed")
+        });
+    }
+
+    if let Some(p) = &args.xor_file {
+        let data = open_data(args, p)?;
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::xor::write_xor(writer, data, out).context("Writing XORed data to output failed")
+        });
+    }
+
+    if let Some(p) = &args.template {
+        let template =
+            std::fs::read(p).context(format!("Reading template failed: {}", p.display()))?;
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::template::write_template(writer, &template, out)
+                .context("Writing template to output failed")
+        });
+    }
+
+    if let Some(count) = args.words {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::words::write_words(writer, count, &mut text_output(out, args))
+                .context("Writing words to output failed")
+        });
+    }
+
+    if args.utf8 {
+        let length = args
+            .length
+            .expect("Length is required by the options parser with --utf8");
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::utf8::write_utf8(writer, length, out).context("Writing text to output failed")
+        });
+    }
+
+    if args.framed {
+        let length = args
+            .length
+            .expect("Length is required by the options parser with --framed");
+        let record_size = args
+            .record_size
+            .expect("Record size is required by the options parser with --framed");
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::framed::write_frames(writer, length, record_size, out)
+                .context("Writing frames to output failed")
+        });
+    }
+
+    if args.inline_timing {
+        let length = args
+            .length
+            .expect("Length is required by the options parser with --inline-timing");
+        let record_size = args
+            .record_size
+            .expect("Record size is required by the options parser with --inline-timing");
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::inline_timing::write_timed_frames(
+                writer,
+                length,
+                record_size,
+                args.max_delay,
+                out,
+            )
+            .context("Writing timed frames to output failed")
+        });
+    }
+
+    #[cfg(feature = "datetimes")]
+    if let Some(count) = args.datetimes {
+        let range = args
+            .date_range
+            .expect("Date range is required by the options parser with --datetimes");
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::datetimes::write_datetimes(writer, count, range, &mut text_output(out, args))
+                .context("Writing times to output failed")
+        });
+    }
+
+    if let Some(count) = args.ips {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::ips::write_ips(
+                writer,
+                count,
+                args.ipv6,
+                args.ports,
+                &mut text_output(out, args),
+            )
+            .context("Writing IP addresses to output failed")
+        });
+    }
+
+    if let Some(total) = args.fs_image {
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return match &args.output {
+            Some(p) => write_fs_image(writer, total, p).map(|()| ExitCode::SUCCESS),
+            None => write_output(args, |out| {
+                modes::fs_image::write_image(writer, total, out, modes::fs_image::zero_hole)
+                    .context("Writing image to output failed")
+            }),
+        };
+    }
+
+    if let Some(layout) = &args.layout {
+        let records = args
+            .records
+            .expect("Records count is required by the options parser");
+
+        let separator = if args.null_separator {
+            &[0][..]
+        } else {
+            args.separator.as_ref().map_or(&[][..], |s| &s.0)
+        };
+
+        let writer = InfiniteDataWriter::from_seed_bytes(seed.internal()?);
+        return write_output(args, |out| {
+            modes::layout::write_records(writer, layout, records, separator, out)
+                .context("Writing records to output failed")
+        });
+    }
+
+    if let Some(p) = &args.resume {
+        return resume(args, p);
+    }
+    if args.checkpoint.is_some() {
+        check_checkpoint_generator(args)?;
+    }
+
+    let (start_offset, length) = stream_range(args, seed)?;
+
+    if let Some(p) = &args.output {
+        if args.append {
+            check_append_offset(p, start_offset)?;
+        }
+        if args.check_space {
+            output::check_available_space(p, length, file_mode(args)).map_err(CliError::output)?;
+        }
+    }
+
+    if args.reverse || args.converge {
+        return write_seeking_output(args, seed, start_offset, length).map(|()| ExitCode::SUCCESS);
+    }
+
+    if args.crlf && !args.hexdump && args.base64_wrap.is_none() && args.format == Format::Raw {
+        return Err(CliError::invalid_options(
+            "--crlf is only available for text output, which raw data is not",
+        ));
+    }
+
+    let blocks = match (args.generator, args.hash) {
+        (Generator::Counter, _) => stats::Blocks::Counter,
+        (Generator::Chain, HashFunction::Blake2b) => stats::Blocks::Chain {
+            batch_size: args.digest_size.unwrap_or(64) as u64,
+        },
+        #[cfg(feature = "sha256")]
+        (Generator::Chain, HashFunction::Sha256) => stats::Blocks::Chain { batch_size: 32 },
+    };
+
+    // Checked before the seed is read, since it would be read from stdin
+    if args.compare_stdin_and_file.is_some() && args.file.is_none() && args.seed.is_none() {
+        return Err(CliError::invalid_options(
+            "The seed must be given with --seed or --file with --compare-stdin-and-file",
+        ));
+    }
+
+    let data_writer = data_source(args, seed, start_offset, length)?;
+
+    if let Some(p) = &args.compare_stream {
+        let reference: Box<dyn Read> = if p.as_os_str() == "-" {
+            if args.file.is_none() && args.seed.is_none() && args.seed_delimiter.is_none() {
+                return Err(CliError::invalid_options(
+                    "The seed must be given with --seed, --file or --seed-delimiter when the reference stream is read from stdin",
+                ));
+            }
+            Box::new(std::io::stdin().lock())
+        } else {
+            Box::new(open_file(p)?)
+        };
+
+        let comparison = modes::compare_stream::compare_stream(data_writer, reference)
+            .context("Reading the reference stream failed")?;
+        report_comparison(comparison, "reference stream");
+        return Ok(ExitCode::from(comparison.exit_code()));
+    }
+
+    if let Some(p) = &args.compare_stdin_and_file {
+        let mut file = open_file(p)?;
+        let mut stdin = std::io::stdin().lock();
+        let [file_comparison, stdin_comparison] = modes::compare_stream::compare_streams(
+            data_writer,
+            [&mut file as &mut dyn Read, &mut stdin],
+        )
+        .context("Reading the file or stdin failed")?;
+
+        report_source_comparison(file_comparison, "file");
+        report_source_comparison(stdin_comparison, "stdin");
+        return Ok(ExitCode::from(
+            file_comparison
+                .exit_code()
+                .max(stdin_comparison.exit_code()),
+        ));
+    }
+
+    #[cfg(feature = "compression")]
+    if args.roundtrip.is_some() {
+        return modes::roundtrip::run(args, data_writer);
+    }
+
+    if let Some(other_args) = &args.assert_eq {
+        let comparison = compare_configurations(args, data_writer, other_args, length)?;
+        report_comparison(comparison, "stream of --assert-eq");
+        return Ok(ExitCode::from(comparison.exit_code()));
+    }
+
+    {
+        let mut run_stats = stats::RunStats::start(blocks, start_offset, !args.no_seed_hash);
+        let mut digest_check = args
+            .check_digest
+            .as_ref()
+            .map(|d| observers::DigestCheck::new(d.0.clone()))
+            .transpose()?;
+
+        let mut observers: Vec<&mut dyn observers::Observer> = vec![&mut run_stats];
+        if let Some(c) = &mut digest_check {
+            observers.push(c);
+        }
+        let mut entropy_report = args.entropy_report.then(observers::EntropyReport::new);
+        if let Some(r) = &mut entropy_report {
+            observers.push(r);
+        }
+        let mut rate_report = args.rate_report.then(observers::RateReport::new);
+        if let Some(r) = &mut rate_report {
+            observers.push(r);
+        }
+        #[cfg(feature = "crc32")]
+        let mut crc32 = args.crc32.then(observers::Crc32::new);
+        #[cfg(feature = "crc32")]
+        if let Some(c) = &mut crc32 {
+            observers.push(c);
+        }
+        #[cfg(feature = "xxhash")]
+        let mut xxhash = args.xxhash.then(observers::XxHash64::new);
+        #[cfg(feature = "xxhash")]
+        if let Some(h) = &mut xxhash {
+            observers.push(h);
+        }
+        let mut preview = args
+            .preview
+            .map(|length| observers::Preview::new(std::io::stderr(), length));
+        if let Some(p) = &mut preview {
+            observers.push(p);
+        }
+
+        let timing_profile = args
+            .timing
+            .as_ref()
+            .map(|p| {
+                let text = std::fs::read_to_string(p)
+                    .context(format!("Reading timing profile failed: {}", p.display()))?;
+                output::TimingProfile::parse(&text)
+            })
+            .transpose()?;
+
+        let prefix = args.prefix.as_ref().map_or(&[][..], |p| &p.0);
+        let suffix = args.suffix.as_ref().map_or(&[][..], |s| &s.0);
+        let mode = if args.prealloc {
+            output::FileMode::Preallocate(prefix.len() as u64 + length + suffix.len() as u64)
+        } else {
+            file_mode(args)
+        };
+
+        let exit_code = write_output_with_mode(args, mode, |out| {
+            let out = checkpointed(out, args, 0, length);
+            let mut out: Box<dyn Write + '_> = match timing_profile {
+                Some(profile) => Box::new(output::Paced::new(out, profile)),
+                None => out,
+            };
+            if let Some(burst) = args.burst {
+                out = Box::new(output::Bursty::new(out, burst));
+            }
+            #[cfg(all(unix, feature = "signals"))]
+            if args.pause_signals {
+                out = Box::new(output::Pausable::new(out));
+            }
+
+            if args.crlf {
+                out = Box::new(output::LineEndings::new(out, true));
+            }
+            // Outermost, so that everything written before an interrupt is flushed through all the layers
+            #[cfg(all(unix, feature = "signals"))]
+            {
+                out = Box::new(output::Interruptible::new(out));
+            }
+            match (args.hexdump, args.format) {
+                (true, _) => output::write_encoded_stream(
+                    prefix,
+                    data_writer,
+                    suffix,
+                    output::HexDump::new(),
+                    &mut out,
+                    &mut observers,
+                ),
+                (false, _) if args.base64_wrap.is_some() => output::write_encoded_stream(
+                    prefix,
+                    data_writer,
+                    suffix,
+                    output::Base64::wrapped(args.base64_wrap.expect("Checked by the guard")),
+                    &mut out,
+                    &mut observers,
+                ),
+                (false, Format::Raw) => {
+                    output::write_stream(prefix, data_writer, suffix, &mut out, &mut observers)
+                }
+                (false, Format::Hex) => output::write_encoded_stream(
+                    prefix,
+                    data_writer,
+                    suffix,
+                    output::Hex,
+                    &mut out,
+                    &mut observers,
+                ),
+                #[cfg(feature = "base32")]
+                (false, Format::Base32) => output::write_encoded_stream(
+                    prefix,
+                    data_writer,
+                    suffix,
+                    output::Base32::new(),
+                    &mut out,
+                    &mut observers,
+                ),
+                #[cfg(feature = "base58")]
+                (false, Format::Base58) => output::write_encoded_stream(
+                    prefix,
+                    data_writer,
+                    suffix,
+                    output::Base58::new(),
+                    &mut out,
+                    &mut observers,
+                ),
+            }
+        })?;
+        if exit_code != ExitCode::SUCCESS {
+            return Ok(exit_code);
+        }
+
+        if let Some(p) = preview {
+            p.finish().context("Writing preview to stderr failed")?;
+        }
+
+        if args.stats {
+            run_stats
+                .write_report(&mut std::io::stderr())
+                .context("Writing stats to stderr failed")?;
+        }
+
+        if let Some(r) = entropy_report {
+            r.write_report(&mut std::io::stderr())
+                .context("Writing entropy report to stderr failed")?;
+        }
+
+        if let Some(r) = rate_report {
+            r.write_report(&mut std::io::stderr())
+                .context("Writing rate report to stderr failed")?;
+        }
+
+        #[cfg(feature = "crc32")]
+        if let Some(c) = crc32 {
+            eprintln!("crc32: {:08x}", c.finish());
+        }
+
+        #[cfg(feature = "xxhash")]
+        if let Some(h) = xxhash {
+            eprintln!("xxhash64: {:016x}", h.finish());
+        }
+
+        if let Some(c) = digest_check {
+            let expected = hex::encode(c.expected());
+            let outcome = c.finish();
+            if let observers::DigestCheckOutcome::Mismatch { actual } = &outcome {
+                eprintln!(
+                    "Digest mismatch: expected {expected}, but the generated data has {}",
+                    hex::encode(actual)
+                );
+            }
+            return Ok(ExitCode::from(outcome.exit_code()));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Open the output, run the given write operation on it, then flush and finish the output,
+/// returning the exit code of the program.
+fn write_output(
+    args: &program_options::RDGenOptions,
+    write: impl FnOnce(&mut output::Output) -> anyhow::Result<()>,
+) -> anyhow::Result<ExitCode> {
+    write_output_with_mode(args, file_mode(args), write)
+}
+
+/// Same as `write_output`, with the given mode for opening the output file, if any
+fn write_output_with_mode(
+    args: &program_options::RDGenOptions,
+    mode: output::FileMode,
+    write: impl FnOnce(&mut output::Output) -> anyhow::Result<()>,
+) -> anyhow::Result<ExitCode> {
+    let destination = match (&args.output, &args.pipe_to) {
+        (Some(p), _) => output::Destination::File(p, mode),
+        (None, Some(command)) => output::Destination::Command(command),
+        (None, None) => output::Destination::Stdout,
+    };
+
+    let mut output_handle =
+        output::Output::open(destination, args.flush_every, args.max_chunk, args.align)
+            .map_err(CliError::output)?;
+    let result = output::write_and_flush(&mut output_handle, write);
+    output_handle
+        .finish(result)
+        .map(ExitCode::from)
+        .map_err(CliError::output)
+}
+
+/// Write the output file with --reverse or --converge, which write its blocks out of order
+fn write_seeking_output(
+    args: &program_options::RDGenOptions,
+    seed: &RunSeed,
+    start_offset: u64,
+    length: u64,
+) -> anyhow::Result<()> {
+    let option = if args.converge {
+        "--converge"
+    } else {
+        "--reverse"
+    };
+    if args.generator != Generator::Counter || args.hash != HashFunction::Blake2b {
+        return Err(CliError::invalid_options(format!(
+            "{option} is only available with the counter generator"
+        )));
+    }
+    let p = args
+        .output
+        .as_ref()
+        .expect("Output is required by the options parser with --reverse and --converge");
+
+    let mut writer = CounterDataWriter::from_seed_bytes(seed.internal()?, None)
+        .with_counter_width(args.counter_width);
+    let end = start_offset.checked_add(length).ok_or_else(|| {
+        CliError::invalid_options("The start offset plus the length is too large")
+    })?;
+    writer
+        .seek_to(end)
+        .map_err(|e| CliError::invalid_options(e.to_string()))?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(!args.no_truncate)
+        .open(p)
+        .context(format!("Creating output file failed: {}", p.display()))
+        .map_err(CliError::output)?;
+    let result = if args.converge {
+        output::write_converging(writer, start_offset, length, &mut file)
+    } else {
+        output::write_reverse(writer, start_offset, length, &mut file)
+    };
+    result
+        .context(format!("Writing output file failed: {}", p.display()))
+        .map_err(CliError::output)
+}
+
+/// Write a filesystem image to the given file, with real holes
+fn write_fs_image(
+    writer: InfiniteDataWriter,
+    total: u64,
+    p: &std::path::Path,
+) -> anyhow::Result<()> {
+    let write = || -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(p)?);
+        modes::fs_image::write_image(writer, total, &mut out, modes::fs_image::seek_hole)?;
+        let file = out.into_inner().map_err(|e| e.into_error())?;
+        file.set_len(total)
+    };
+    write()
+        .context(format!(
+            "Writing image to output file failed: {}",
+            p.display()
+        ))
+        .map_err(CliError::output)
+}
+
+/// Continue the output file of an interrupted run from the given checkpoint
+fn resume(args: &program_options::RDGenOptions, p: &std::path::Path) -> anyhow::Result<ExitCode> {
+    check_checkpoint_generator(args)?;
+    let checkpoint = checkpoint::Checkpoint::read(p)?;
+
+    let output_path = args
+        .output
+        .as_ref()
+        .expect("Output is required by the options parser with --resume");
+    let output_file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(output_path)
+        .context(format!(
+            "Opening output file failed: {}",
+            output_path.display()
+        ))
+        .map_err(CliError::output)?;
+    let existing_size = output_file
+        .metadata()
+        .context(format!(
+            "Reading output file metadata failed: {}",
+            output_path.display()
+        ))?
+        .len();
+    if existing_size < checkpoint.offset {
+        return Err(CliError::invalid_options(format!(
+            "Cannot resume at offset {} of {}, which has {existing_size} bytes",
+            checkpoint.offset,
+            output_path.display()
+        )));
+    }
+    output_file
+        .set_len(checkpoint.offset)
+        .context(format!(
+            "Truncating output file failed: {}",
+            output_path.display()
+        ))
+        .map_err(CliError::output)?;
+
+    let data_writer = FiniteDataWriter::from_seed_bytes(
+        checkpoint.state,
+        Some(checkpoint.length - checkpoint.offset),
+    );
+    write_output_with_mode(args, output::FileMode::Append, |out| {
+        let mut out = checkpointed(out, args, checkpoint.offset, checkpoint.length);
+        output::write_stream(&[], data_writer, &[], &mut out, &mut [])
+    })
+}
+
+/// Checkpoints are taken from the output of the hash chain, so other generators are not supported
+fn check_checkpoint_generator(args: &program_options::RDGenOptions) -> anyhow::Result<()> {
+    if args.generator != Generator::Chain || args.hash != HashFunction::Blake2b {
+        return Err(CliError::invalid_options(
+            "Checkpoints are only available with the chain generator and Blake2b",
+        ));
+    }
+    Ok(())
+}
+
+/// The output, taking checkpoints if --checkpoint is given, where the data written starts at `offset`
+/// of an output of `length` bytes
+fn checkpointed<'a>(
+    out: &'a mut output::Output,
+    args: &program_options::RDGenOptions,
+    offset: u64,
+    length: u64,
+) -> Box<dyn Write + 'a> {
+    match &args.checkpoint {
+        Some(p) => Box::new(checkpoint::Checkpointing::new(
+            out,
+            p.clone(),
+            args.checkpoint_every,
+            offset,
+            length,
+        )),
+        None => Box::new(out),
+    }
+}
+
+/// The output of a mode that writes text, with the line endings of --crlf
+fn text_output<'a>(
+    out: &'a mut output::Output,
+    args: &program_options::RDGenOptions,
+) -> output::LineEndings<&'a mut output::Output> {
+    output::LineEndings::new(out, args.crlf)
+}
+
+fn file_mode(args: &program_options::RDGenOptions) -> output::FileMode {
+    if args.append {
+        output::FileMode::Append
+    } else if args.fill_output || args.no_truncate {
+        output::FileMode::InPlace
+    } else {
+        output::FileMode::Truncate
+    }
+}
+
+/// The size of the existing file or device of --output, which is found by seeking to its end,
+/// since the size in the metadata of devices is zero
+fn output_size(args: &program_options::RDGenOptions) -> anyhow::Result<u64> {
+    use std::io::{Seek, SeekFrom};
+
+    let p = args
+        .output
+        .as_ref()
+        .expect("Output is required by --fill-output");
+    let unknown_size = || {
+        CliError::invalid_options(format!(
+            "The size of the output is unknown, so it cannot be filled: {}",
+            p.display()
+        ))
+    };
+
+    let metadata = std::fs::metadata(p)
+        .context(format!("Reading output metadata failed: {}", p.display()))
+        .map_err(CliError::output)?;
+    if metadata.is_file() {
+        return Ok(metadata.len());
+    }
+    if metadata.is_dir() {
+        return Err(unknown_size());
+    }
+    // Opening a pipe would wait for the other end, so pipes are rejected before opening the output
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if metadata.file_type().is_fifo() || metadata.file_type().is_socket() {
+            return Err(unknown_size());
+        }
+    }
+
+    let mut f = std::fs::File::open(p)
+        .context(format!("Opening output failed: {}", p.display()))
+        .map_err(CliError::output)?;
+    f.seek(SeekFrom::End(0)).map_err(|_| unknown_size())
+}
+
+/// The length of the output with --min-length, picked from the seed
+fn picked_length(seed: &RunSeed, min_length: u64, max_length: u64) -> anyhow::Result<u64> {
+    if min_length > max_length {
+        return Err(CliError::invalid_options(format!(
+            "The minimum length {min_length} is larger than the length {max_length}"
+        )));
+    }
+    #[cfg(feature = "sha256")]
+    if seed.args.hash == HashFunction::Sha256 {
+        return Err(CliError::invalid_options(
+            "--min-length is not available with SHA-256",
+        ));
+    }
+    Ok(pick_length(seed.internal()?, min_length, max_length))
+}
+
+/// Pick a length from `min_length` to `max_length`, both included, uniformly with `uniform_below` over the bytes of
+/// the generator forked by mixing in a fixed context, so that the length does not change the generated data
+fn pick_length(seed: [u8; 64], min_length: u64, max_length: u64) -> u64 {
+    let mut writer = InfiniteDataWriter::from_seed_bytes(seed);
+    writer.mix_in(b"rdgen length");
+    let mut bytes = writer.flatten();
+    match (max_length - min_length).checked_add(1) {
+        Some(count) => min_length + modes::sampling::uniform_below(&mut bytes, count),
+        // Every u64 is a length in the range
+        None => u64::from_le_bytes(std::array::from_fn(|_| {
+            modes::sampling::next_byte(&mut bytes)
+        })),
+    }
+}
+
+/// The size of the seed in bytes, when it is known without reading the seed
+fn seed_length(args: &program_options::RDGenOptions) -> anyhow::Result<u64> {
+    #[cfg(feature = "seed-url")]
+    if args.seed_url.is_some() {
+        return Err(CliError::invalid_options(
+            "--match-seed-length is not available with --seed-url",
+        ));
+    }
+
+    let suffix_length = args.seed_suffix.as_ref().map_or(0, |s| s.len() as u64);
+    let source_length = |length: u64| args.seed_bytes.map_or(length, |k| length.min(k));
+    match (&args.file, &args.seed) {
+        (Some(p), _) => Ok(source_length(
+            std::fs::metadata(p)
+                .context(format!(
+                    "Reading seed file metadata failed: {}",
+                    p.display()
+                ))?
+                .len(),
+        ) + suffix_length),
+        (None, Some(seed)) => Ok(source_length(seed.len() as u64) + suffix_length),
+        (None, None) => Err(CliError::invalid_options(
+            "--match-seed-length requires the seed to be given with --file or --seed",
+        )),
+    }
+}
+
+fn write_metadata(
+    args: &program_options::RDGenOptions,
+    seed: &RunSeed,
+    p: &std::path::Path,
+) -> anyhow::Result<()> {
+    fn name(value: impl clap::ValueEnum) -> String {
+        value
+            .to_possible_value()
+            .map_or_else(String::new, |v| v.get_name().to_string())
+    }
+    let length = if args.match_seed_length {
+        Some(seed_length(args)?)
+    } else if args.fill_output {
+        Some(output_size(args)?)
+    } else if let (Some(min_length), Some(length)) = (args.min_length, args.length) {
+        Some(picked_length(seed, min_length, length)?)
+    } else {
+        args.length
+    };
+
+    let metadata = metadata::Metadata {
+        seed_fingerprint: metadata::seed_fingerprint(&seed.internal()?),
+        length,
+        mode: mode_name(args).to_string(),
+        generator: name(args.generator),
+        hash: name(args.hash),
+        digest_size: match args.hash {
+            HashFunction::Blake2b => args.digest_size.unwrap_or(64),
+            #[cfg(feature = "sha256")]
+            HashFunction::Sha256 => 32,
+        },
+        format: match args.base64_wrap {
+            Some(_) => "base64".to_string(),
+            None => name(args.format),
+        },
+        nonce: args.nonce.as_ref().map(|n| n.0.clone()),
+    };
+    metadata
+        .write(p)
+        .context(format!("Writing metadata failed: {}", p.display()))
+        .map_err(CliError::output)
+}
+
+/// The name of the option of the mode of the run, or "raw"
+fn mode_name(args: &program_options::RDGenOptions) -> &'static str {
+    let modes = [
+        ("dump-states", args.dump_states.is_some()),
+        ("derive-seeds", args.derive_seeds.is_some()),
+        ("log-lines", args.log_lines.is_some()),
+        ("numbered-lines", args.numbered_lines.is_some()),
+        ("kv-lines", args.kv_lines.is_some()),
+        ("choices-file", args.choices_file.is_some()),
+        ("shuffle-lines", args.shuffle_lines.is_some()),
+        ("corrupt", args.corrupt.is_some()),
+        ("xor-file", args.xor_file.is_some()),
+        ("template", args.template.is_some()),
+        ("words", args.words.is_some()),
+        ("utf8", args.utf8),
+        ("framed", args.framed),
+        ("inline-timing", args.inline_timing),
+        ("ips", args.ips.is_some()),
+        #[cfg(feature = "datetimes")]
+        ("datetimes", args.datetimes.is_some()),
+        ("fs-image", args.fs_image.is_some()),
+        ("layout", args.layout.is_some()),
+        #[cfg(feature = "listen")]
+        ("listen", args.listen.is_some()),
+    ];
+    modes
+        .iter()
+        .find(|(_, present)| *present)
+        .map_or("raw", |(name, _)| name)
+}
+
+/// Whether `p` exists and was modified strictly later than `reference`
+fn is_newer_than(p: &std::path::Path, reference: &std::path::Path) -> anyhow::Result<bool> {
+    let modified = |p: &std::path::Path| {
+        std::fs::metadata(p)
+            .and_then(|m| m.modified())
+            .context(format!(
+                "Reading file modification time failed: {}",
+                p.display()
+            ))
+    };
+
+    match std::fs::metadata(p) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        _ => Ok(modified(p)? > modified(reference)?),
+    }
+}
+
+/// Make sure that the file being appended to ends exactly where the appended data starts in the stream,
+/// so that the file is a seamless continuation of the stream.
+fn check_append_offset(p: &std::path::Path, start_offset: u64) -> anyhow::Result<()> {
+    let existing_size = match std::fs::metadata(p) {
+        Ok(m) => m.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => {
+            return Err(e).context(format!(
+                "Reading output file metadata failed: {}",
+                p.display()
+            ))
+        }
+    };
+
+    if existing_size != start_offset {
+        return Err(CliError::invalid_options(format!(
+            "Cannot append at offset {start_offset} to {}, which has {existing_size} bytes",
+            p.display()
+        )));
+    }
+
+    Ok(())
+}
+
+/// The start offset in the stream and the length of the generated data
+fn stream_range(
+    args: &program_options::RDGenOptions,
+    seed: &RunSeed,
+) -> anyhow::Result<(u64, u64)> {
+    let mut length = if args.match_seed_length {
+        seed_length(args)?
+    } else if args.fill_output {
+        output_size(args)?
+    } else {
+        let length = args
+            .length
+            .expect("Length is required by the options parser outside of other modes");
+        match args.min_length {
+            Some(min_length) => picked_length(seed, min_length, length)?,
+            None => length,
+        }
+    };
+
+    let mut start_offset = args.start_offset.unwrap_or(0);
+
+    if let Some(shard) = &args.shard {
+        if args.generator != Generator::Counter {
+            return Err(CliError::invalid_options(
+                "--shard is only available with the counter generator",
+            ));
+        }
+        let (shard_offset, shard_length) = shard.range(length);
+        start_offset = start_offset.checked_add(shard_offset).ok_or_else(|| {
+            CliError::invalid_options("The start offset of the shard is too large")
+        })?;
+        length = shard_length;
+    }
+
+    Ok((start_offset, length))
+}
+
+/// The source of the generated data of the stream with the given range, with the generator of the options
+fn data_source(
+    args: &program_options::RDGenOptions,
+    seed: &RunSeed,
+    start_offset: u64,
+    length: u64,
+) -> anyhow::Result<Box<dyn DataSource>> {
+    let data_writer: Box<dyn DataSource> = match (args.generator, args.hash, &args.interleave) {
+        (generator, hash, _) if args.digest_size.is_some() => {
+            if generator != Generator::Chain || hash != HashFunction::Blake2b {
+                return Err(CliError::invalid_options(
+                    "--digest-size is only available with the chain generator and Blake2b",
+                ));
+            }
+            let writer = seed.read()?.variable.clone();
+            let writer = writer.expect("The seed is read with the digest size of the options");
+            Box::new(Chunks::new(stream::take_bytes(
+                stream::skip_bytes(writer, start_offset),
+                length,
+            )))
+        }
+        (Generator::Chain, HashFunction::Blake2b, Some(other_seed)) => {
+            Box::new(Chunks::new(stream::take_bytes(
+                stream::skip_bytes(
+                    rdgen_lib::interleave_writers(
+                        InfiniteDataWriter::from_seed_bytes(seed.internal()?),
+                        InfiniteDataWriter::new(other_seed),
+                        args.interleave_block,
+                    ),
+                    start_offset,
+                ),
+                length,
+            )))
+        }
+        (Generator::Chain, HashFunction::Blake2b, None) => {
+            let total_length = start_offset.checked_add(length).ok_or_else(|| {
+                CliError::invalid_options("The start offset plus the length is too large")
+            })?;
+            let writer = FiniteDataWriter::from_seed_bytes(seed.internal()?, Some(total_length));
+            if start_offset == 0 {
+                Box::new(writer)
+            } else {
+                Box::new(Chunks::new(stream::skip_bytes(writer, start_offset)))
+            }
+        }
+        #[cfg(feature = "sha256")]
+        (Generator::Chain, HashFunction::Sha256, None) => {
+            if args.no_seed_hash {
+                return Err(CliError::invalid_options(
+                    "--no-seed-hash is not available with SHA-256",
+                ));
+            }
+            let writer = seed.read()?.sha256.clone();
+            let writer = writer.expect("The seed is read with the hash function of the options");
+            Box::new(Chunks::new(stream::take_bytes(
+                stream::skip_bytes(writer.map(|batch| batch.to_vec()), start_offset),
+                length,
+            )))
+        }
+        (Generator::Counter, HashFunction::Blake2b, _) => {
+            let mut writer = CounterDataWriter::from_seed_bytes(seed.internal()?, Some(length))
+                .with_counter_width(args.counter_width);
+            writer
+                .seek_to(start_offset)
+                .map_err(|e| CliError::invalid_options(e.to_string()))?;
+            Box::new(writer)
+        }
+        #[cfg(feature = "sha256")]
+        (_, HashFunction::Sha256, _) => {
+            return Err(CliError::invalid_options(
+                "SHA-256 is only available with the chain generator, without --interleave",
+            ))
+        }
+    };
+    Ok(match args.serial {
+        Some(serial) => Box::new(stream::Overlay::new(
+            data_writer,
+            serial.to_le_bytes().to_vec(),
+            args.serial_offset,
+        )),
+        None => data_writer,
+    })
+}
+
+/// Print the outcome of the comparison of the generated data with the given other stream
+fn report_comparison(comparison: StreamComparison, other: &str) {
+    match comparison {
+        StreamComparison::Match { length } => {
+            println!("Streams match: {length} bytes compared")
+        }
+        StreamComparison::Mismatch { offset } => {
+            eprintln!("Streams differ at offset {offset}")
+        }
+        StreamComparison::ReferenceTooShort { length } => {
+            eprintln!("The {other} ends after {length} matching bytes")
+        }
+        StreamComparison::ReferenceTooLong { length } => {
+            eprintln!("The {other} has more bytes after {length} matching bytes")
+        }
+    }
+}
+
+/// Report the comparison of one of the sources of --compare-stdin-and-file with the generated stream,
+/// naming the source, since they are all reported together
+fn report_source_comparison(comparison: StreamComparison, source: &str) {
+    match comparison {
+        StreamComparison::Match { length } => {
+            println!("The {source} matches the generated stream: {length} bytes compared")
+        }
+        StreamComparison::Mismatch { offset } => {
+            eprintln!("The {source} differs from the generated stream at offset {offset}")
+        }
+        StreamComparison::ReferenceTooShort { length } => {
+            eprintln!("The {source} ends after {length} matching bytes")
+        }
+        StreamComparison::ReferenceTooLong { length } => {
+            eprintln!("The {source} has more bytes after {length} matching bytes")
+        }
+    }
+}
+
+/// Compare the generated data with the data of the other configuration of --assert-eq
+fn compare_configurations(
+    args: &program_options::RDGenOptions,
+    data_writer: impl DataSource,
+    other_args: &str,
+    length: u64,
+) -> anyhow::Result<StreamComparison> {
+    let other = assert_eq_options(args, other_args, length)?;
+    let other_seed = RunSeed::new(&other);
+    let (other_start_offset, other_length) = stream_range(&other, &other_seed)?;
+    let other_writer = data_source(&other, &other_seed, other_start_offset, other_length)?;
+
+    modes::compare_stream::compare_stream(data_writer, stream::SourceReader::new(other_writer))
+        .context("Generating the stream of --assert-eq failed")
+}
+
+/// The options of the other configuration of --assert-eq, parsed from the given arguments, which are split
+/// by whitespace. The other configuration has the seed of this run, which the arguments cannot change,
+/// and the given length, unless the arguments give another one. The seed must be given with --seed or --file,
+/// so that it can be read by both configurations.
+fn assert_eq_options(
+    args: &program_options::RDGenOptions,
+    other_args: &str,
+    length: u64,
+) -> anyhow::Result<program_options::RDGenOptions> {
+    use clap::{CommandFactory, FromArgMatches};
+
+    if args.file.is_none() && args.seed.is_none() {
+        return Err(CliError::invalid_options(
+            "The seed must be given with --seed or --file with --assert-eq",
+        ));
+    }
+
+    let length = length.to_string();
+    let other_args = ["rdgen", "--length", &length]
+        .into_iter()
+        .chain(other_args.split_whitespace());
+    let mut other = program_options::RDGenOptions::command()
+        .args_override_self(true)
+        .try_get_matches_from(other_args)
+        .and_then(|matches| program_options::RDGenOptions::from_arg_matches(&matches))
+        .map_err(|e| {
+            // Only the message, without the usage of rdgen
+            let message = e.to_string();
+            let message = message.lines().next().unwrap_or_default();
+            let message = message.strip_prefix("error: ").unwrap_or(message);
+            CliError::invalid_options(format!("Invalid arguments of --assert-eq: {message}"))
+        })?;
+
+    #[cfg(feature = "seed-url")]
+    let seed_url = other.seed_url.is_some();
+    #[cfg(not(feature = "seed-url"))]
+    let seed_url = false;
+    if other.file.is_some()
+        || other.seed.is_some()
+        || seed_url
+        || other.seed_line.is_some()
+        || other.seed_suffix.is_some()
+        || other.seed_delimiter.is_some()
+        || other.seed_bytes.is_some()
+        || other.no_seed_hash
+        || other.require_seed
+        || other.nonce.is_some()
+    {
+        return Err(CliError::invalid_options(
+            "The arguments of --assert-eq cannot change the seed",
+        ));
+    }
+    if other.assert_eq.is_some()
+        || other.compare_stream.is_some()
+        || other.compare_stdin_and_file.is_some()
+        || mode_name(&other) != "raw"
+    {
+        return Err(CliError::invalid_options(
+            "The arguments of --assert-eq must configure a stream of raw data",
+        ));
+    }
+
+    other.file = args.file.clone();
+    other.seed = args.seed.clone();
+    other.seed_line = args.seed_line;
+    other.seed_suffix = args.seed_suffix.clone();
+    other.seed_bytes = args.seed_bytes;
+    other.no_seed_hash = args.no_seed_hash;
+    other.require_seed = args.require_seed;
+    other.nonce = args.nonce.clone();
+    Ok(other)
+}
+
+/// The seed of a run, which is read on first use, and only once, since stdin and URLs cannot be read again
+struct RunSeed<'a> {
+    args: &'a program_options::RDGenOptions,
+    /// The whole seed, once it is read by --derive-seeds
+    bytes: std::cell::OnceCell<Vec<u8>>,
+    seeds: std::cell::OnceCell<InternalSeeds>,
+}
+
+impl<'a> RunSeed<'a> {
+    fn new(args: &'a program_options::RDGenOptions) -> Self {
+        Self {
+            args,
+            bytes: std::cell::OnceCell::new(),
+            seeds: std::cell::OnceCell::new(),
+        }
+    }
+
+    /// The internal seed of the Blake2b generators, which is the Blake2b hash of the seed, or, with --no-seed-hash,
+    /// the seed itself, which must then be exactly 64 bytes, with the nonce mixed in
+    fn internal(&self) -> anyhow::Result<[u8; 64]> {
+        Ok(self.read()?.blake2b)
+    }
+
+    /// The internal seeds of every generator of the run
+    fn read(&self) -> anyhow::Result<&InternalSeeds> {
+        if let Some(seeds) = self.seeds.get() {
+            return Ok(seeds);
         }
-    };
+        let mut seeds = match self.bytes.get() {
+            Some(bytes) => read_internal_seeds(self.args, bytes.as_slice()),
+            None => open_seed_source(self.args)
+                .and_then(|source| read_internal_seeds(self.args, source)),
+        }
+        .map_err(CliError::seed_source)?;
+        if let Some(nonce) = &self.args.nonce {
+            seeds.mix_in(&nonce.0);
+        }
+        Ok(self.seeds.get_or_init(|| seeds))
+    }
 
-    {
-        let stdout = std::io::stdout();
-        let mut stdout_handle = stdout.lock();
+    /// The whole seed, for --derive-seeds, which derives the seeds from it instead of an internal seed
+    fn bytes(&self) -> anyhow::Result<&[u8]> {
+        if let Some(bytes) = self.bytes.get() {
+            return Ok(bytes);
+        }
+        let mut bytes = Vec::new();
+        open_seed_source(self.args)
+            .and_then(|mut source| {
+                source
+                    .read_to_end(&mut bytes)
+                    .context("Reading the seed failed")
+            })
+            .map_err(CliError::seed_source)?;
+        Ok(self.bytes.get_or_init(|| bytes))
+    }
+}
+
+/// The internal seeds that the generators of a run derive from its seed
+struct InternalSeeds {
+    /// The internal seed of the Blake2b generators
+    blake2b: [u8; 64],
+    /// The hash chain of --digest-size, which hashes the seed with its digest size
+    variable: Option<rdgen_lib::VariableDataWriter>,
+    /// The hash chain of --hash sha256, which hashes the seed with SHA-256
+    #[cfg(feature = "sha256")]
+    sha256: Option<rdgen_lib::Sha256DataWriter>,
+}
+
+impl InternalSeeds {
+    /// Mix the nonce into every internal seed, each with its own hash function
+    fn mix_in(&mut self, nonce: &[u8]) {
+        let mut writer = InfiniteDataWriter::from_seed_bytes(self.blake2b);
+        writer.mix_in(nonce);
+        self.blake2b = *writer.seed();
+        if let Some(writer) = &mut self.variable {
+            writer.mix_in(nonce);
+        }
+        #[cfg(feature = "sha256")]
+        if let Some(writer) = &mut self.sha256 {
+            writer.mix_in(nonce);
+        }
+    }
+}
+
+/// Read the seed from the source, and derive from it, in the same read, the internal seeds of the generators
+/// of the options
+fn read_internal_seeds(
+    args: &program_options::RDGenOptions,
+    source: impl Read,
+) -> anyhow::Result<InternalSeeds> {
+    if args.no_seed_hash {
+        // The parser and data_source reject --digest-size and SHA-256 with --no-seed-hash
+        return Ok(InternalSeeds {
+            blake2b: read_exact_seed(source)?,
+            variable: None,
+            #[cfg(feature = "sha256")]
+            sha256: None,
+        });
+    }
+
+    let mut source = HashingReader::new(source);
+    let variable = args
+        .digest_size
+        .map(|digest_size| rdgen_lib::VariableDataWriter::new_from_stream(&mut source, digest_size))
+        .transpose()?;
+    #[cfg(feature = "sha256")]
+    let sha256 = (args.hash == HashFunction::Sha256 && args.digest_size.is_none())
+        .then(|| rdgen_lib::Sha256DataWriter::new_from_stream(&mut source))
+        .transpose()?;
+    // The rest of the seed, if no other hash function has read it
+    std::io::copy(&mut source, &mut std::io::sink()).context("Reading the seed failed")?;
+    Ok(InternalSeeds {
+        blake2b: source.finalize(),
+        variable,
+        #[cfg(feature = "sha256")]
+        sha256,
+    })
+}
+
+/// A reader that hashes the bytes it reads with Blake2b, so that other hash functions can read the seed
+/// in the same read
+struct HashingReader<R> {
+    inner: R,
+    hasher: blake2::Blake2b512,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: blake2::Digest::new(),
+        }
+    }
+
+    fn finalize(self) -> [u8; 64] {
+        blake2::Digest::finalize(self.hasher).into()
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        blake2::Digest::update(&mut self.hasher, &buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Read the seed of --no-seed-hash, which must be exactly 64 bytes
+fn read_exact_seed(source: impl Read) -> anyhow::Result<[u8; 64]> {
+    // One byte more than needed is read, to detect seeds that are too long
+    let mut seed = Vec::with_capacity(65);
+    source
+        .take(65)
+        .read_to_end(&mut seed)
+        .context("Reading the seed failed")?;
+    match seed.try_into() {
+        Ok(seed) => Ok(seed),
+        Err(seed) if seed.len() > 64 => Err(anyhow::anyhow!(
+            "With --no-seed-hash, the seed must be exactly 64 bytes, but it is longer"
+        )),
+        Err(seed) => Err(anyhow::anyhow!(
+            "With --no-seed-hash, the seed must be exactly 64 bytes, but it has {} bytes",
+            seed.len()
+        )),
+    }
+}
+
+/// Open the source of the seed, which is either the file provided, the seed in the command line,
+/// the body of the seed URL, or stdin.
+fn open_seed_source(args: &program_options::RDGenOptions) -> anyhow::Result<Box<dyn Read>> {
+    let mut source = open_seed_source_without_suffix(args)?;
+    if let Some(k) = args.seed_bytes {
+        source = Box::new(source.take(k));
+    }
+    if args.require_seed {
+        source = require_non_empty(source)?;
+    }
+    Ok(match &args.seed_suffix {
+        Some(suffix) => Box::new(source.chain(std::io::Cursor::new(suffix.clone().into_bytes()))),
+        None => source,
+    })
+}
+
+/// Fail if the source is empty, or return a source with the same bytes
+fn require_non_empty(mut source: Box<dyn Read>) -> anyhow::Result<Box<dyn Read>> {
+    let mut first = [0];
+    loop {
+        match source.read(&mut first) {
+            Ok(0) => {
+                return Err(anyhow::anyhow!(
+                    "The seed is empty, and --require-seed was given"
+                ))
+            }
+            Ok(_) => return Ok(Box::new(std::io::Cursor::new(first).chain(source))),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(anyhow::Error::new(e).context("Reading the seed failed")),
+        }
+    }
+}
+
+fn open_seed_source_without_suffix(
+    args: &program_options::RDGenOptions,
+) -> anyhow::Result<Box<dyn Read>> {
+    #[cfg(feature = "seed-url")]
+    if let Some(url) = &args.seed_url {
+        return open_url(url);
+    }
+
+    match (&args.file, &args.seed) {
+        (Some(f), _) => match args.seed_line {
+            Some(n) => {
+                let line = read_line(open_file(f)?, n)
+                    .context(format!("Reading seed file failed: {}", f.display()))?;
+                Ok(Box::new(std::io::Cursor::new(line)))
+            }
+            None => Ok(Box::new(open_file(f)?)),
+        },
+        (None, Some(seed)) => Ok(Box::new(std::io::Cursor::new(seed.clone().into_bytes()))),
+        (None, None) => {
+            check_stdin_seed(std::io::stdin().is_terminal())?;
+            match &args.seed_delimiter {
+                Some(delimiter) => {
+                    let seed =
+                        read_until_delimiter(&mut std::io::stdin().lock(), delimiter.as_bytes())
+                            .context("Reading the seed from stdin failed")?
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("The seed delimiter was not found in stdin")
+                            })?;
+                    Ok(Box::new(std::io::Cursor::new(seed)))
+                }
+                None => Ok(Box::new(std::io::stdin())),
+            }
+        }
+    }
+}
 
-        for data in data_writer {
-            stdout_handle
-                .write_all(&data)
-                .expect("Writing result to stdout failing")
+#[cfg(feature = "seed-url")]
+fn open_url(url: &str) -> anyhow::Result<Box<dyn Read>> {
+    let response = ureq::get(url)
+        .call()
+        .context(format!("Downloading the seed failed: {url}"))?;
+    Ok(Box::new(response.into_body().into_reader()))
+}
+
+/// Read the source up to the first occurrence of the delimiter, and return what comes before it, or None
+/// if the source ends first. Nothing after the delimiter is consumed, so the source can be read further.
+fn read_until_delimiter(
+    source: &mut impl std::io::BufRead,
+    delimiter: &[u8],
+) -> std::io::Result<Option<Vec<u8>>> {
+    let Some(&last) = delimiter.last() else {
+        return Ok(Some(Vec::new()));
+    };
+    let mut read = Vec::new();
+    loop {
+        if source.read_until(last, &mut read)? == 0 {
+            return Ok(None);
+        }
+        if read.ends_with(delimiter) {
+            read.truncate(read.len() - delimiter.len());
+            return Ok(Some(read));
         }
     }
+}
+
+/// Open the data of a mode that reads a file, which is the rest of stdin after the seed with --seed-delimiter
+/// and the path "-"
+fn open_data(
+    args: &program_options::RDGenOptions,
+    p: &std::path::Path,
+) -> anyhow::Result<Box<dyn Read>> {
+    if args.seed_delimiter.is_some() && p.as_os_str() == "-" {
+        return Ok(Box::new(std::io::stdin()));
+    }
+    Ok(Box::new(open_file(p)?))
+}
+
+/// Read the line with the given number, where the first line is 1, without its line ending
+fn read_line(source: impl std::io::BufRead, number: u64) -> anyhow::Result<Vec<u8>> {
+    let mut lines = source.split(b'\n');
+    let mut line = match lines.nth((number - 1) as usize) {
+        Some(line) => line?,
+        None => return Err(anyhow::anyhow!("There is no line {number}")),
+    };
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(line)
+}
 
+/// Reading the seed from an interactive terminal would silently wait for the user to type it,
+/// so it is refused with an explanation of how to provide the seed.
+fn check_stdin_seed(stdin_is_terminal: bool) -> anyhow::Result<()> {
+    if stdin_is_terminal {
+        return Err(anyhow::anyhow!(
+            "No seed provided. Pipe the seed into rdgen (e.g. echo -n \"abc\" | rdgen -l100), \
+            or use --seed <SEED> or --file <PATH>"
+        ));
+    }
     Ok(())
 }
 
-fn open_file(p: impl AsRef<std::path::Path>) -> anyhow::Result<impl Read> {
+fn open_file(p: impl AsRef<std::path::Path>) -> anyhow::Result<BufReader<std::fs::File>> {
     let p = p.as_ref();
     if !p.exists() {
         return Err(anyhow::anyhow!("File not found: {}", p.display()));
@@ -49,5 +1614,1336 @@ fn open_file(p: impl AsRef<std::path::Path>) -> anyhow::Result<impl Read> {
 
     let f = std::fs::File::open(p).context(format!("Opening file failed: {}", p.display()))?;
 
-    Ok(f)
+    Ok(BufReader::new(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffered_file_seed_hash() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "abc").unwrap();
+
+        let mut from_file =
+            InfiniteDataWriter::new_from_stream(open_file(file.path()).unwrap()).unwrap();
+        let mut expected = InfiniteDataWriter::new("abc");
+        assert_eq!(from_file.seed(), expected.seed());
+        assert_eq!(from_file.pull(), expected.pull());
+    }
+
+    #[test]
+    fn seed_from_terminal() {
+        let e = check_stdin_seed(true).unwrap_err();
+        assert!(e.to_string().contains("--seed"));
+        check_stdin_seed(false).unwrap();
+    }
+
+    #[cfg(feature = "seed-url")]
+    #[test]
+    fn seed_from_url() {
+        use std::io::Write;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the request headers before responding
+            let mut request = Vec::new();
+            let mut byte = [0];
+            while !request.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                request.push(byte[0]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\nConnection: close\r\n\r\nabc")
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let url = format!("http://{address}/seed");
+        let writer = InfiniteDataWriter::new_from_stream(open_url(&url).unwrap()).unwrap();
+        assert_eq!(writer.seed(), InfiniteDataWriter::new("abc").seed());
+        assert!(server.join().unwrap().starts_with("GET /seed "));
+
+        // Nothing listens on the port anymore
+        let e = open_url(&url).err().unwrap();
+        assert!(e.to_string().contains("Downloading the seed failed"));
+    }
+
+    #[test]
+    fn seed_from_command_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        run_with(&["--seed", "abc", "-l100", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+    }
+
+    #[test]
+    fn assert_eq_compares_configurations() {
+        let compare = |other_args: &str| {
+            let args = program_options::RDGenOptions::try_parse_from([
+                "rdgen",
+                "-s",
+                "abc",
+                "-l1000",
+                "--assert-eq",
+                other_args,
+            ])
+            .unwrap();
+            let seed = RunSeed::new(&args);
+            let (start_offset, length) = stream_range(&args, &seed).unwrap();
+            let data_writer = data_source(&args, &seed, start_offset, length).unwrap();
+            compare_configurations(&args, data_writer, other_args, length)
+        };
+
+        for same in ["", "--generator chain", "--digest-size 64", "-l 1000"] {
+            assert_eq!(
+                compare(same).unwrap(),
+                StreamComparison::Match { length: 1000 },
+                "{same}"
+            );
+        }
+        assert_eq!(
+            compare("--serial 7 --serial-offset 100").unwrap(),
+            StreamComparison::Mismatch { offset: 100 }
+        );
+        assert_eq!(
+            compare("--generator counter").unwrap(),
+            StreamComparison::Mismatch { offset: 0 }
+        );
+        assert_eq!(
+            compare("-l 600").unwrap(),
+            StreamComparison::ReferenceTooShort { length: 600 }
+        );
+
+        for invalid in [
+            "--seed abd",
+            "--nonce 01",
+            "--seed-bytes 2",
+            "--words 3",
+            "--bogus",
+        ] {
+            let e = compare(invalid).unwrap_err();
+            assert_eq!(
+                errors::exit_code(&e),
+                exit_codes::INVALID_OPTIONS,
+                "{invalid}"
+            );
+        }
+
+        assert_eq!(
+            run_with(&["-s", "abc", "-l1000", "--assert-eq", "--generator counter"]).unwrap(),
+            ExitCode::from(exit_codes::MISMATCH)
+        );
+        assert_eq!(
+            run_with(&["-s", "abc", "-l1000", "--assert-eq", "--digest-size 64"]).unwrap(),
+            ExitCode::SUCCESS
+        );
+        // The other configuration reads the seed again, with the nonce and the limit of this run
+        assert_eq!(
+            run_with(&[
+                "-s",
+                "abc",
+                "-l1000",
+                "--nonce",
+                "01",
+                "--seed-bytes",
+                "2",
+                "--assert-eq",
+                ""
+            ])
+            .unwrap(),
+            ExitCode::SUCCESS
+        );
+    }
+
+    #[test]
+    fn derived_seeds_as_hex_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("seeds.txt");
+        let output_str = output.to_str().unwrap();
+
+        run_with(&["-s", "abc", "--derive-seeds", "3", "-o", output_str]).unwrap();
+        let expected: String = rdgen_lib::derive_seeds(b"abc", 3)
+            .iter()
+            .map(|child| format!("{}\n", hex::encode(child)))
+            .collect();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), expected);
+    }
+
+    #[test]
+    fn rate_report_keeps_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        for args in [
+            &["-s", "abc", "-l100000", "--rate-report", "-o", output_str][..],
+            &[
+                "-s",
+                "abc",
+                "-l100000",
+                "--rate-report",
+                "--burst",
+                "10000:1",
+                "-o",
+                output_str,
+            ],
+        ] {
+            run_with(args).unwrap();
+            let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100000))
+                .flatten()
+                .collect();
+            assert_eq!(std::fs::read(&output).unwrap(), expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn nonce_reproduces_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let metadata = dir.path().join("output.json");
+        let (output_str, metadata_str) = (output.to_str().unwrap(), metadata.to_str().unwrap());
+        let run = |nonce: &str| {
+            run_with(&[
+                "-s",
+                "abc",
+                "-l1000",
+                "--nonce",
+                nonce,
+                "-o",
+                output_str,
+                "--metadata",
+                metadata_str,
+            ])
+            .unwrap();
+            std::fs::read(&output).unwrap()
+        };
+
+        let data = run("0102");
+        let json = std::fs::read_to_string(&metadata).unwrap();
+        assert!(json.contains(r#""nonce": "0102""#), "{json}");
+        assert_eq!(run("0102"), data);
+        assert_ne!(run("0103"), data);
+
+        let mut writer = InfiniteDataWriter::new("abc");
+        writer.mix_in(&[1, 2]);
+        let expected: Vec<u8> = FiniteDataWriter::from_seed_bytes(*writer.seed(), Some(1000))
+            .flatten()
+            .collect();
+        assert_eq!(data, expected);
+        let unmixed: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+        assert_ne!(data, unmixed);
+    }
+
+    #[test]
+    fn nonce_changes_every_generator() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        let run = |generator_args: &[&str], nonce: Option<&str>| {
+            let mut args = vec!["-s", "abc", "-l1000", "-o", output_str];
+            args.extend_from_slice(generator_args);
+            if let Some(nonce) = nonce {
+                args.extend(["--nonce", nonce]);
+            }
+            run_with(&args).unwrap();
+            std::fs::read(&output).unwrap()
+        };
+
+        let generators: &[&[&str]] = &[
+            &[],
+            &["--generator", "counter"],
+            &["--generator", "counter", "--counter-width", "4"],
+            &["--interleave", "other"],
+            &["--digest-size", "32"],
+            &["--digest-size", "64"],
+            #[cfg(feature = "sha256")]
+            &["--hash", "sha256"],
+        ];
+        for &generator_args in generators {
+            let data = run(generator_args, Some("0102"));
+            assert_eq!(
+                run(generator_args, Some("0102")),
+                data,
+                "{generator_args:?}"
+            );
+            assert_ne!(
+                run(generator_args, Some("0103")),
+                data,
+                "{generator_args:?}"
+            );
+            assert_ne!(run(generator_args, None), data, "{generator_args:?}");
+        }
+        // The full digest size is the stream of the default generator, with the nonce too
+        assert_eq!(
+            run(&["--digest-size", "64"], Some("0102")),
+            run(&[], Some("0102"))
+        );
+    }
+
+    #[test]
+    fn corrupt_flips_bits_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.bin");
+        let data: Vec<u8> = FiniteDataWriter::new("data", Some(1000))
+            .flatten()
+            .collect();
+        std::fs::write(&input, &data).unwrap();
+        let input = input.to_str().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        let run = |seed: &str, count: &str| {
+            run_with(&[
+                "-s",
+                seed,
+                "--corrupt",
+                count,
+                "--corrupt-input",
+                input,
+                "-o",
+                output_str,
+            ])
+            .unwrap();
+            std::fs::read(&output).unwrap()
+        };
+        let corrupted = run("abc", "25");
+        let changed_bits: u32 = data
+            .iter()
+            .zip(&corrupted)
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum();
+        assert_eq!(changed_bits, 25);
+        assert_eq!(run("abc", "25"), corrupted);
+        assert_ne!(run("abd", "25"), corrupted);
+
+        let e =
+            run_with(&["-s", "abc", "--corrupt", "8001", "--corrupt-input", input]).unwrap_err();
+        assert_eq!(errors::exit_code(&e), exit_codes::INVALID_OPTIONS);
+    }
+
+    #[test]
+    fn seed_bytes_hashes_prefix_of_seed() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abcdefghij").unwrap();
+        let seed = seed.to_str().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+
+        for args in [
+            &["-f", seed, "--seed-bytes", "3", "-l100", "-o", output_str][..],
+            &[
+                "-s",
+                "abcdefghij",
+                "--seed-bytes",
+                "3",
+                "-l100",
+                "-o",
+                output_str,
+            ],
+            &[
+                "-s",
+                "abc",
+                "--seed-bytes",
+                "1000",
+                "-l100",
+                "-o",
+                output_str,
+            ],
+        ] {
+            run_with(args).unwrap();
+            assert_eq!(std::fs::read(&output).unwrap(), expected, "{args:?}");
+        }
+
+        run_with(&[
+            "-f",
+            seed,
+            "--seed-bytes",
+            "3",
+            "--match-seed-length",
+            "-o",
+            output_str,
+        ])
+        .unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expected[..3]);
+    }
+
+    #[test]
+    fn require_seed_rejects_empty_seed() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "").unwrap();
+        let seed = seed.to_str().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        run_with(&["-f", seed, "-l100", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        for args in [
+            &["-f", seed, "--require-seed", "-l100", "-o", output_str][..],
+            &["--seed", "", "--require-seed", "-l100", "-o", output_str],
+            &[
+                "--seed",
+                "",
+                "--require-seed",
+                "--seed-suffix",
+                "x",
+                "-l100",
+                "-o",
+                output_str,
+            ],
+        ] {
+            let e = run_with(args).unwrap_err();
+            assert_eq!(errors::exit_code(&e), exit_codes::SEED_SOURCE, "{args:?}");
+            assert!(format!("{e:#}").contains("The seed is empty"));
+        }
+
+        run_with(&["-s", "abc", "--require-seed", "-l100", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+    }
+
+    fn run_with(args: &[&str]) -> anyhow::Result<ExitCode> {
+        let args = program_options::RDGenOptions::try_parse_from(
+            std::iter::once("rdgen").chain(args.iter().copied()),
+        )?;
+        run(args)
+    }
+
+    #[test]
+    fn append_continues_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let seed = seed.to_str().unwrap();
+
+        for generator in ["chain", "counter"] {
+            let single = dir.path().join(format!("single-{generator}.bin"));
+            let appended = dir.path().join(format!("appended-{generator}.bin"));
+            let single = single.to_str().unwrap();
+            let appended = appended.to_str().unwrap();
+
+            let common = ["-f", seed, "--generator", generator];
+            run_with(&[&common[..], &["-l200", "-o", single]].concat()).unwrap();
+            run_with(&[&common[..], &["-l100", "-o", appended]].concat()).unwrap();
+            run_with(
+                &[
+                    &common[..],
+                    &["-l100", "-o", appended, "--append", "--start-offset", "100"],
+                ]
+                .concat(),
+            )
+            .unwrap();
+
+            let single = std::fs::read(single).unwrap();
+            assert_eq!(single.len(), 200);
+            assert_eq!(std::fs::read(appended).unwrap(), single);
+        }
+    }
+
+    #[test]
+    fn append_at_wrong_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let output = dir.path().join("output.bin");
+        std::fs::write(&output, [0; 10]).unwrap();
+
+        let seed = seed.to_str().unwrap();
+        let output_str = output.to_str().unwrap();
+        let result = run_with(&[
+            "-f",
+            seed,
+            "-l10",
+            "-o",
+            output_str,
+            "--append",
+            "--start-offset",
+            "20",
+        ]);
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(&output).unwrap(), [0; 10]);
+    }
+
+    #[test]
+    fn raw_seed_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed_path = dir.path().join("seed.bin");
+        let seed: [u8; 64] = std::array::from_fn(|i| i as u8);
+        std::fs::write(&seed_path, seed).unwrap();
+        let output = dir.path().join("output.bin");
+
+        let seed_str = seed_path.to_str().unwrap();
+        let output_str = output.to_str().unwrap();
+        for generator in ["chain", "counter"] {
+            let common = [
+                "-f",
+                seed_str,
+                "-o",
+                output_str,
+                "-l200",
+                "--generator",
+                generator,
+            ];
+            run_with(&[&common[..], &["--no-seed-hash"]].concat()).unwrap();
+            let raw = std::fs::read(&output).unwrap();
+            run_with(&common).unwrap();
+            assert_ne!(std::fs::read(&output).unwrap(), raw);
+
+            let expected: Vec<u8> = match generator {
+                "chain" => FiniteDataWriter::from_seed_bytes(seed, Some(200))
+                    .flatten()
+                    .collect(),
+                _ => CounterDataWriter::from_seed_bytes(seed, Some(200))
+                    .flatten()
+                    .collect(),
+            };
+            assert_eq!(raw, expected);
+        }
+
+        for wrong_length in [0, 63, 65] {
+            std::fs::write(&seed_path, vec![0; wrong_length]).unwrap();
+            let result = run_with(&["-f", seed_str, "-o", output_str, "-l10", "--no-seed-hash"]);
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn compare_stdin_and_file_requires_seed_option() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.bin");
+        std::fs::write(&file, b"data").unwrap();
+        let file = file.to_str().unwrap();
+
+        let e = run_with(&["-l100", "--compare-stdin-and-file", file]).unwrap_err();
+        assert_eq!(errors::exit_code(&e), exit_codes::INVALID_OPTIONS);
+        assert!(run_with(&[
+            "-s",
+            "abc",
+            "-l100",
+            "--compare-stdin-and-file",
+            file,
+            "-o",
+            file
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn compare_stream_with_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let reference = dir.path().join("reference.bin");
+        let reference_str = reference.to_str().unwrap();
+
+        let common = ["-s", "abc", "-l1000", "--generator", "counter"];
+        run_with(&[&common[..], &["-o", reference_str]].concat()).unwrap();
+        let code = run_with(&[&common[..], &["--compare-stream", reference_str]].concat());
+        assert_eq!(code.unwrap(), ExitCode::SUCCESS);
+
+        let mut altered = std::fs::read(&reference).unwrap();
+        altered[500] ^= 1;
+        std::fs::write(&reference, altered).unwrap();
+        let code = run_with(&[&common[..], &["--compare-stream", reference_str]].concat());
+        assert_eq!(code.unwrap(), ExitCode::from(exit_codes::MISMATCH));
+    }
+
+    #[test]
+    fn shards_reassemble() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let output = dir.path().join("output.bin");
+
+        let seed = seed.to_str().unwrap();
+        let output_str = output.to_str().unwrap();
+        let common = ["-f", seed, "-o", output_str, "--generator", "counter"];
+
+        run_with(&[&common[..], &["-l201"]].concat()).unwrap();
+        let single = std::fs::read(&output).unwrap();
+
+        for total in [1, 2, 3, 7] {
+            let mut reassembled = Vec::new();
+            for index in 0..total {
+                let shard = format!("{index}/{total}");
+                run_with(&[&common[..], &["-l201", "--shard", &shard]].concat()).unwrap();
+                reassembled.extend(std::fs::read(&output).unwrap());
+            }
+            assert_eq!(reassembled, single);
+        }
+
+        assert!(run_with(&["-f", seed, "-o", output_str, "-l10", "--shard", "0/2"]).is_err());
+    }
+
+    #[test]
+    fn zero_length_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let choices = dir.path().join("choices.txt");
+        std::fs::write(&choices, "a\nb\n").unwrap();
+        let output = dir.path().join("output.bin");
+
+        let seed = seed.to_str().unwrap();
+        let choices = choices.to_str().unwrap();
+        let output_str = output.to_str().unwrap();
+        let runs: [&[&str]; 8] = [
+            &["-l0"],
+            &["-l0", "--generator", "counter"],
+            &["-l0", "--interleave", "xyz"],
+            &["-l0", "--stats", "--start-offset", "100"],
+            &["--log-lines", "0"],
+            &["--words", "0"],
+            &["--layout", "u32,bytes[3]", "--records", "0"],
+            &["--choices-file", choices, "--choices-count", "0"],
+        ];
+        for run in runs {
+            let code = run_with(&[&["-f", seed, "-o", output_str][..], run].concat()).unwrap();
+            assert_eq!(code, ExitCode::SUCCESS);
+            assert!(std::fs::read(&output).unwrap().is_empty());
+        }
+
+        // Only the markers are written
+        run_with(&[
+            "-f", seed, "-o", output_str, "-l0", "--prefix", "ab", "--suffix", "cd",
+        ])
+        .unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), [0xab, 0xcd]);
+
+        // The seed is still validated
+        let missing = dir.path().join("missing.txt");
+        assert!(run_with(&["-f", missing.to_str().unwrap(), "-o", output_str, "-l0"]).is_err());
+    }
+
+    #[test]
+    fn start_offset_skips_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let output = dir.path().join("output.bin");
+
+        let seed = seed.to_str().unwrap();
+        let output_str = output.to_str().unwrap();
+        run_with(&["-f", seed, "-l50", "-o", output_str, "--start-offset", "70"]).unwrap();
+
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(120)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected[70..]);
+    }
+
+    #[test]
+    fn if_newer_skips_up_to_date_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let output = dir.path().join("output.bin");
+        let set_modified = |p: &std::path::Path, seconds: u64| {
+            let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+            std::fs::File::options()
+                .write(true)
+                .open(p)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+
+        let seed = seed.as_path();
+        let args = [
+            "-f",
+            seed.to_str().unwrap(),
+            "-l10",
+            "-o",
+            output.to_str().unwrap(),
+            "--if-newer",
+            output.to_str().unwrap(),
+        ];
+
+        // No output yet
+        run_with(&args).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap().len(), 10);
+
+        // The output is newer than the seed
+        std::fs::write(&output, "old").unwrap();
+        set_modified(seed, 1_000_000);
+        set_modified(&output, 2_000_000);
+        run_with(&args).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), b"old");
+
+        // The same time
+        set_modified(&output, 1_000_000);
+        run_with(&args).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap().len(), 10);
+
+        // The seed is newer than the output
+        std::fs::write(&output, "old").unwrap();
+        set_modified(seed, 3_000_000);
+        set_modified(&output, 2_000_000);
+        run_with(&args).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn match_seed_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "0123456789").unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        let seed_str = seed.to_str().unwrap();
+        run_with(&["-f", seed_str, "--match-seed-length", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("0123456789", Some(10))
+            .flatten()
+            .collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        run_with(&["-s", "abc", "--match-seed-length", "-o", output_str]).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap().len(), 3);
+
+        assert!(run_with(&["--match-seed-length", "-o", output_str]).is_err());
+    }
+
+    #[test]
+    fn prealloc_writes_over_reserved_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        std::fs::write(&output, vec![1; 1000]).unwrap();
+        let output_str = output.to_str().unwrap();
+
+        run_with(&["-s", "abc", "-l100", "-o", output_str, "--prealloc"]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        run_with(&[
+            "-s",
+            "abc",
+            "-l100",
+            "-o",
+            output_str,
+            "--prealloc",
+            "--prefix",
+            "ff",
+        ])
+        .unwrap();
+        let data = std::fs::read(&output).unwrap();
+        assert_eq!(data.len(), 101);
+        assert_eq!(data[1..], expected);
+    }
+
+    #[test]
+    fn seed_suffix_extends_seed() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = dir.path().join("seed.txt");
+        std::fs::write(&seed, "abc").unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        let seed_str = seed.to_str().unwrap();
+        run_with(&[
+            "-f",
+            seed_str,
+            "-l100",
+            "-o",
+            output_str,
+            "--seed-suffix",
+            "1234",
+        ])
+        .unwrap();
+        let with_suffix = std::fs::read(&output).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc1234", Some(100))
+            .flatten()
+            .collect();
+        assert_eq!(with_suffix, expected);
+
+        run_with(&[
+            "-s",
+            "abc",
+            "-l100",
+            "-o",
+            output_str,
+            "--seed-suffix",
+            "1234",
+        ])
+        .unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        run_with(&["-f", seed_str, "-l100", "-o", output_str]).unwrap();
+        assert_ne!(std::fs::read(&output).unwrap(), with_suffix);
+    }
+
+    #[test]
+    fn failure_exit_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        let missing = dir.path().join("missing");
+        let missing_output = missing.join("output.bin");
+
+        let exit_code = |args: &[&str]| errors::exit_code(&run_with(args).unwrap_err());
+
+        let seed_file = missing.to_str().unwrap();
+        assert_eq!(
+            exit_code(&["-f", seed_file, "-l10", "-o", output_str]),
+            exit_codes::SEED_SOURCE
+        );
+        assert_eq!(
+            exit_code(&["-s", "abc", "--no-seed-hash", "-l10", "-o", output_str]),
+            exit_codes::SEED_SOURCE
+        );
+        assert_eq!(
+            exit_code(&["-s", "abc", "-l10", "-o", missing_output.to_str().unwrap()]),
+            exit_codes::OUTPUT
+        );
+        assert_eq!(
+            exit_code(&["-s", "abc", "-l10", "-o", output_str, "--shard", "0/2"]),
+            exit_codes::INVALID_OPTIONS
+        );
+        assert_eq!(
+            exit_code(&[
+                "-s",
+                "abc",
+                "-l10",
+                "-o",
+                output_str,
+                "--start-offset",
+                "5",
+                "--append"
+            ]),
+            exit_codes::INVALID_OPTIONS
+        );
+
+        std::fs::write(&output, "not the stream").unwrap();
+        let exit_code = run_with(&["-s", "abc", "--compare-hashes", output_str]).unwrap();
+        assert_eq!(exit_code, ExitCode::from(exit_codes::MISMATCH));
+    }
+
+    #[test]
+    fn seed_line_selects_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let seeds = dir.path().join("seeds.txt");
+        std::fs::write(&seeds, "first\nabc\r\nthird\n").unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        let seeds = seeds.to_str().unwrap();
+        run_with(&["-f", seeds, "--seed-line", "2", "-l100", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        run_with(&["-f", seeds, "--seed-line", "3", "-l100", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("third", Some(100))
+            .flatten()
+            .collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        let result = run_with(&["-f", seeds, "--seed-line", "4", "-l100", "-o", output_str]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::SEED_SOURCE
+        );
+    }
+
+    #[test]
+    fn fill_output_keeps_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        std::fs::File::create(&output)
+            .unwrap()
+            .set_len(100)
+            .unwrap();
+
+        run_with(&["-s", "abc", "--fill-output", "-o", output_str]).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        let result = run_with(&[
+            "-s",
+            "abc",
+            "--fill-output",
+            "-o",
+            dir.path().to_str().unwrap(),
+        ]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::INVALID_OPTIONS
+        );
+        assert!(run_with(&["-s", "abc", "--fill-output", "-l10", "-o", output_str]).is_err());
+    }
+
+    #[test]
+    fn truncate_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(10)).flatten().collect();
+
+        for args in [
+            &["-s", "abc", "-l10", "-o", output_str][..],
+            &["-s", "abc", "-l10", "--truncate", "-o", output_str],
+            &[
+                "-s",
+                "abc",
+                "-l10",
+                "--no-truncate",
+                "--truncate",
+                "-o",
+                output_str,
+            ],
+        ] {
+            std::fs::write(&output, [0xff; 100]).unwrap();
+            run_with(args).unwrap();
+            assert_eq!(std::fs::read(&output).unwrap(), expected, "{args:?}");
+        }
+
+        for args in [
+            &["-s", "abc", "-l10", "--no-truncate", "-o", output_str][..],
+            &[
+                "-s",
+                "abc",
+                "-l10",
+                "--truncate",
+                "--no-truncate",
+                "-o",
+                output_str,
+            ],
+            &[
+                "-s",
+                "abc",
+                "-l10",
+                "--generator",
+                "counter",
+                "--reverse",
+                "--no-truncate",
+                "-o",
+                output_str,
+            ],
+        ] {
+            std::fs::write(&output, [0xff; 100]).unwrap();
+            run_with(args).unwrap();
+            let data = std::fs::read(&output).unwrap();
+            let expected = if args.contains(&"counter") {
+                CounterDataWriter::new("abc", Some(10)).flatten().collect()
+            } else {
+                expected.clone()
+            };
+            assert_eq!(data[..10], expected, "{args:?}");
+            assert_eq!(data[10..], [0xff; 90], "{args:?}");
+        }
+
+        std::fs::remove_file(&output).unwrap();
+        run_with(&["-s", "abc", "-l10", "--no-truncate", "-o", output_str]).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        assert!(run_with(&[
+            "-s",
+            "abc",
+            "-l10",
+            "--no-truncate",
+            "--append",
+            "--start-offset",
+            "10",
+            "-o",
+            output_str
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn digest_size_sets_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        for digest_size in [16, 32, 64] {
+            let size = digest_size.to_string();
+            run_with(&[
+                "-s",
+                "abc",
+                "-l100",
+                "--digest-size",
+                &size,
+                "-o",
+                output_str,
+            ])
+            .unwrap();
+            let expected: Vec<u8> = rdgen_lib::VariableDataWriter::new("abc", digest_size)
+                .unwrap()
+                .flatten()
+                .take(100)
+                .collect();
+            assert_eq!(std::fs::read(&output).unwrap(), expected);
+        }
+
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+        for invalid in ["0", "65"] {
+            assert!(run_with(&["-s", "abc", "-l100", "--digest-size", invalid]).is_err());
+        }
+        let result = run_with(&[
+            "-s",
+            "abc",
+            "-l100",
+            "--digest-size",
+            "32",
+            "--generator",
+            "counter",
+        ]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::INVALID_OPTIONS
+        );
+    }
+
+    #[test]
+    fn internal_seeds_from_one_read() {
+        let parse = |extra: &[&str]| {
+            program_options::RDGenOptions::try_parse_from(
+                ["rdgen", "-l10"].iter().chain(extra).copied(),
+            )
+            .unwrap()
+        };
+        let blake2b = *InfiniteDataWriter::new("abc").seed();
+
+        let seeds = read_internal_seeds(&parse(&[]), &b"abc"[..]).unwrap();
+        assert_eq!(seeds.blake2b, blake2b);
+        assert!(seeds.variable.is_none());
+
+        let seeds = read_internal_seeds(&parse(&["--digest-size", "32"]), &b"abc"[..]).unwrap();
+        assert_eq!(seeds.blake2b, blake2b);
+        let expected = rdgen_lib::VariableDataWriter::new("abc", 32)
+            .unwrap()
+            .pull();
+        assert_eq!(seeds.variable.unwrap().pull(), expected);
+
+        #[cfg(feature = "sha256")]
+        {
+            let seeds = read_internal_seeds(&parse(&["--hash", "sha256"]), &b"abc"[..]).unwrap();
+            assert_eq!(seeds.blake2b, blake2b);
+            let expected = rdgen_lib::Sha256DataWriter::new("abc").pull();
+            assert_eq!(seeds.sha256.unwrap().pull(), expected);
+        }
+    }
+
+    #[test]
+    fn min_length_picks_stable_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+
+        let mut lengths = std::collections::HashSet::new();
+        for seed in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            run_with(&["-s", seed, "--min-length", "100", "-l200", "-o", output_str]).unwrap();
+            let data = std::fs::read(&output).unwrap();
+            assert!((100..=200).contains(&data.len()), "{}", data.len());
+            let expected: Vec<u8> = FiniteDataWriter::new(seed, Some(data.len() as u64))
+                .flatten()
+                .collect();
+            assert_eq!(data, expected);
+            lengths.insert(data.len());
+        }
+        assert!(lengths.len() > 1);
+
+        let seed = *InfiniteDataWriter::new("abc").seed();
+        assert_eq!(pick_length(seed, 100, 200), 151);
+        assert_eq!(pick_length(seed, 100, 200), pick_length(seed, 100, 200));
+        assert_eq!(pick_length(seed, 5, 5), 5);
+        assert!(pick_length(seed, 0, u64::MAX) > 0);
+        let result = run_with(&["-s", "abc", "--min-length", "300", "-l200"]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::INVALID_OPTIONS
+        );
+    }
+
+    #[test]
+    fn delimiter_splits_seed_from_data() {
+        let mut input = std::io::Cursor::new(b"abc\n--\ndata\n--\nmore".to_vec());
+        let seed = read_until_delimiter(&mut input, b"\n--\n").unwrap();
+        assert_eq!(seed.as_deref(), Some(&b"abc"[..]));
+        let mut rest = Vec::new();
+        input.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"data\n--\nmore");
+
+        let mut input = std::io::Cursor::new(b"a-b--c".to_vec());
+        assert_eq!(
+            read_until_delimiter(&mut input, b"--").unwrap().as_deref(),
+            Some(&b"a-b"[..])
+        );
+        let mut input = std::io::Cursor::new(b"abc-".to_vec());
+        assert_eq!(read_until_delimiter(&mut input, b"--").unwrap(), None);
+    }
+
+    #[test]
+    fn words_reject_generator_options() {
+        for generator_args in [
+            &["--generator", "counter"][..],
+            &["--hash", "blake2b"],
+            &["--counter-width", "4"],
+        ] {
+            let args = [&["rdgen", "-s", "abc", "--words", "3"][..], generator_args].concat();
+            assert!(
+                program_options::RDGenOptions::try_parse_from(args).is_err(),
+                "{generator_args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.txt");
+        let output_str = output.to_str().unwrap();
+
+        run_with(&["-s", "abc", "--words", "10", "-o", output_str]).unwrap();
+        let lf = std::fs::read(&output).unwrap();
+        assert!(lf.contains(&b'\n') && !lf.contains(&b'\r'));
+
+        run_with(&["-s", "abc", "--words", "10", "--crlf", "-o", output_str]).unwrap();
+        let crlf = std::fs::read(&output).unwrap();
+        assert_eq!(
+            String::from_utf8(crlf).unwrap(),
+            String::from_utf8(lf).unwrap().replace('\n', "\r\n")
+        );
+
+        run_with(&["-s", "abc", "-l40", "--hexdump", "--crlf", "-o", output_str]).unwrap();
+        let dump = String::from_utf8(std::fs::read(&output).unwrap()).unwrap();
+        assert_eq!(dump.matches("\r\n").count(), 3);
+        assert_eq!(dump.matches('\n').count(), 3);
+
+        let result = run_with(&["-s", "abc", "-l40", "--crlf", "-o", output_str]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::INVALID_OPTIONS
+        );
+    }
+
+    #[test]
+    fn metadata_describes_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let metadata = dir.path().join("output.json");
+        let (output_str, metadata_str) = (output.to_str().unwrap(), metadata.to_str().unwrap());
+
+        run_with(&[
+            "-s",
+            "abc",
+            "-l100",
+            "-o",
+            output_str,
+            "--metadata",
+            metadata_str,
+        ])
+        .unwrap();
+        let json = std::fs::read_to_string(&metadata).unwrap();
+        for field in [
+            r#""seed_fingerprint": "92f03504774a2fa55a577ef28be5e63a""#,
+            r#""length": 100"#,
+            r#""mode": "raw""#,
+            r#""generator": "chain""#,
+            r#""hash": "blake2b""#,
+            r#""digest_size": 64"#,
+            r#""format": "raw""#,
+            r#""context": null"#,
+            r#""nonce": null"#,
+            concat!(r#""version": ""#, env!("CARGO_PKG_VERSION"), r#"""#),
+        ] {
+            assert!(json.contains(field), "{field} not in {json}");
+        }
+
+        run_with(&[
+            "-s",
+            "abc",
+            "--words",
+            "3",
+            "-o",
+            output_str,
+            "--metadata",
+            metadata_str,
+        ])
+        .unwrap();
+        let json = std::fs::read_to_string(&metadata).unwrap();
+        assert!(json.contains(r#""mode": "words""#));
+        assert!(json.contains(r#""length": null"#));
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        let checkpoint = dir.path().join("checkpoint");
+        let checkpoint_str = checkpoint.to_str().unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+
+        let args = [
+            "-s",
+            "abc",
+            "-l1000",
+            "-o",
+            output_str,
+            "--checkpoint",
+            checkpoint_str,
+        ];
+        run_with(&[&args[..], &["--checkpoint-every", "256"]].concat()).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        // A run that stopped after the checkpoint at 768, with some bytes after it
+        std::fs::write(&output, &expected[..800]).unwrap();
+        run_with(&["--resume", checkpoint_str, "-o", output_str]).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+        // A checkpoint beyond the data of the output
+        std::fs::write(&output, &expected[..700]).unwrap();
+        let result = run_with(&["--resume", checkpoint_str, "-o", output_str]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::INVALID_OPTIONS
+        );
+
+        // A checkpoint that is not at a batch boundary, which is rejected before the output is truncated
+        let state = "00".repeat(64);
+        std::fs::write(
+            &checkpoint,
+            format!("offset 100\nlength 1000\nstate {state}"),
+        )
+        .unwrap();
+        let next_checkpoint = dir.path().join("next_checkpoint");
+        let result = run_with(&[
+            "--resume",
+            checkpoint_str,
+            "-o",
+            output_str,
+            "--checkpoint",
+            next_checkpoint.to_str().unwrap(),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(&output).unwrap(), &expected[..700]);
+    }
+
+    #[test]
+    fn fs_image_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("image.bin");
+        let output_str = output.to_str().unwrap();
+
+        run_with(&["-s", "abc", "--fs-image", "300000", "-o", output_str]).unwrap();
+        let image = std::fs::read(&output).unwrap();
+
+        let mut expected = Vec::new();
+        modes::fs_image::write_image(
+            InfiniteDataWriter::new("abc"),
+            300_000,
+            &mut expected,
+            modes::fs_image::zero_hole,
+        )
+        .unwrap();
+        assert_eq!(image, expected);
+    }
+
+    #[test]
+    fn reverse_matches_forward() {
+        let dir = tempfile::tempdir().unwrap();
+        let forward = dir.path().join("forward.bin");
+        let reverse = dir.path().join("reverse.bin");
+
+        let args = [
+            "-s",
+            "abc",
+            "-l200000",
+            "--generator",
+            "counter",
+            "--start-offset",
+            "10",
+        ];
+        run_with(&[&args[..], &["-o", forward.to_str().unwrap()]].concat()).unwrap();
+        run_with(&[&args[..], &["-o", reverse.to_str().unwrap(), "--reverse"]].concat()).unwrap();
+        assert_eq!(
+            std::fs::read(&forward).unwrap(),
+            std::fs::read(&reverse).unwrap()
+        );
+        std::fs::remove_file(&reverse).unwrap();
+        run_with(&[&args[..], &["-o", reverse.to_str().unwrap(), "--converge"]].concat()).unwrap();
+        assert_eq!(
+            std::fs::read(&forward).unwrap(),
+            std::fs::read(&reverse).unwrap()
+        );
+
+        let result = run_with(&[
+            "-s",
+            "abc",
+            "-l10",
+            "-o",
+            reverse.to_str().unwrap(),
+            "--reverse",
+        ]);
+        assert_eq!(
+            errors::exit_code(&result.unwrap_err()),
+            exit_codes::INVALID_OPTIONS
+        );
+    }
+
+    #[test]
+    fn serial_overlays_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.bin");
+        let output_str = output.to_str().unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100)).flatten().collect();
+
+        run_with(&["-s", "abc", "-l100", "-o", output_str, "--serial", "1234"]).unwrap();
+        let data = std::fs::read(&output).unwrap();
+        assert_eq!(u64::from_le_bytes(data[..8].try_into().unwrap()), 1234);
+        assert_eq!(data[8..], expected[8..]);
+
+        let args = [
+            "-s",
+            "abc",
+            "-l100",
+            "-o",
+            output_str,
+            "--serial",
+            "1234",
+            "--serial-offset",
+            "90",
+        ];
+        run_with(&args).unwrap();
+        let data = std::fs::read(&output).unwrap();
+        assert_eq!(u64::from_le_bytes(data[90..98].try_into().unwrap()), 1234);
+        assert_eq!(data[..90], expected[..90]);
+        assert_eq!(data[98..], expected[98..]);
+
+        // An offset past the end, where the end of the serial overflows, leaves the data as it is
+        let offset = u64::MAX.to_string();
+        let args = [
+            "-s",
+            "abc",
+            "-l100",
+            "-o",
+            output_str,
+            "--serial",
+            "5",
+            "--serial-offset",
+            &offset,
+        ];
+        run_with(&args).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), expected);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn roundtrip_succeeds() {
+        for codec in ["deflate", "zlib", "gzip"] {
+            let code = run_with(&["-s", "abc", "-l100000", "--roundtrip", codec]).unwrap();
+            assert_eq!(code, ExitCode::from(exit_codes::SUCCESS));
+        }
+        assert!(run_with(&["-s", "abc", "-l100", "--roundtrip", "lz4"]).is_err());
+    }
 }
@@ -0,0 +1,98 @@
+//! The sidecar document of --metadata, which describes how the output was generated, in JSON.
+
+use std::path::Path;
+
+use blake2::{Blake2b512, Digest};
+
+/// What the internal seed is hashed with for its fingerprint, so that the fingerprint is not a part of the stream
+const FINGERPRINT_CONTEXT: &[u8] = b"rdgen seed fingerprint";
+
+/// A fingerprint of the internal seed, which identifies the seed without revealing it or the generated data:
+/// the first 16 bytes of the Blake2b-512 hash of a fixed context followed by the internal seed
+pub fn seed_fingerprint(seed: &[u8; 64]) -> [u8; 16] {
+    let hash = Blake2b512::new()
+        .chain_update(FINGERPRINT_CONTEXT)
+        .chain_update(seed)
+        .finalize();
+    hash[..16].try_into().expect("The hash is longer")
+}
+
+/// How the output was generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub seed_fingerprint: [u8; 16],
+    /// The length given for the output, if any, which is the number of bytes of raw output
+    pub length: Option<u64>,
+    /// The name of the option of the mode, or "raw"
+    pub mode: String,
+    pub generator: String,
+    pub hash: String,
+    /// The output size of the hash function, which is the size of every batch
+    pub digest_size: usize,
+    pub format: String,
+    /// The bytes of --nonce mixed into the internal seed, if any
+    pub nonce: Option<Vec<u8>>,
+}
+
+impl Metadata {
+    /// The JSON document, where the context is always null, since no context is mixed into the seed
+    /// from the command line, and the version is the version of rdgen
+    pub fn to_json(&self) -> String {
+        let length = self
+            .length
+            .map_or_else(|| "null".to_string(), |l| l.to_string());
+        let nonce = self
+            .nonce
+            .as_ref()
+            .map_or_else(|| "null".to_string(), |n| format!("\"{}\"", hex::encode(n)));
+        format!(
+            concat!(
+                "{{\n",
+                "  \"seed_fingerprint\": \"{}\",\n",
+                "  \"length\": {},\n",
+                "  \"mode\": \"{}\",\n",
+                "  \"generator\": \"{}\",\n",
+                "  \"hash\": \"{}\",\n",
+                "  \"digest_size\": {},\n",
+                "  \"format\": \"{}\",\n",
+                "  \"context\": null,\n",
+                "  \"nonce\": {},\n",
+                "  \"version\": \"{}\"\n",
+                "}}\n"
+            ),
+            hex::encode(self.seed_fingerprint),
+            length,
+            self.mode,
+            self.generator,
+            self.hash,
+            self.digest_size,
+            self.format,
+            nonce,
+            env!("CARGO_PKG_VERSION"),
+        )
+    }
+
+    pub fn write(&self, p: &Path) -> std::io::Result<()> {
+        std::fs::write(p, self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::InfiniteDataWriter;
+
+    use super::*;
+
+    #[test]
+    fn stable_fingerprint() {
+        let seed = *InfiniteDataWriter::new("abc").seed();
+        assert_eq!(
+            hex::encode(seed_fingerprint(&seed)),
+            "92f03504774a2fa55a577ef28be5e63a"
+        );
+        assert_ne!(
+            seed_fingerprint(&seed),
+            seed_fingerprint(InfiniteDataWriter::new("abd").seed())
+        );
+    }
+}
@@ -0,0 +1,30 @@
+//! Alternative output modes that shape the generated stream into structured data
+//! instead of writing it out as raw bytes.
+
+pub mod choices;
+pub mod compare_dir;
+pub mod compare_hashes;
+pub mod compare_stream;
+pub mod corrupt;
+#[cfg(feature = "datetimes")]
+pub mod datetimes;
+pub mod dump_states;
+pub mod framed;
+pub mod fs_image;
+pub mod inline_timing;
+pub mod ips;
+pub mod kv_lines;
+pub mod layout;
+#[cfg(feature = "listen")]
+pub mod listen;
+pub mod log_lines;
+pub mod numbered_lines;
+pub mod prefix_match;
+#[cfg(feature = "compression")]
+pub mod roundtrip;
+pub mod sampling;
+pub mod shuffle_lines;
+pub mod template;
+pub mod utf8;
+pub mod words;
+pub mod xor;
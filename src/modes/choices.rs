@@ -0,0 +1,79 @@
+use std::io::{Read, Write};
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::uniform_below;
+
+/// Read the newline-separated choices. Carriage returns before newlines and empty lines are ignored.
+pub fn read_choices(mut source: impl Read) -> std::io::Result<Vec<Vec<u8>>> {
+    let mut data = Vec::new();
+    source.read_to_end(&mut data)?;
+
+    let choices = data
+        .split(|b| *b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_vec())
+        .collect();
+    Ok(choices)
+}
+
+/// Write `count` choices, one per line, where every choice is picked uniformly using the generated stream.
+pub fn write_choices(
+    writer: InfiniteDataWriter,
+    choices: &[Vec<u8>],
+    count: u64,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    assert!(!choices.is_empty());
+
+    let mut bytes = writer.flatten();
+    for _ in 0..count {
+        let index = uniform_below(&mut bytes, choices.len() as u64) as usize;
+        out.write_all(&choices[index])?;
+        out.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHOICES: &str = "alice\r\nbob\n\ncarol\ndave\n";
+
+    fn picks(seed: &str, count: u64) -> Vec<String> {
+        let choices = read_choices(CHOICES.as_bytes()).unwrap();
+        let mut result = Vec::new();
+        write_choices(InfiniteDataWriter::new(seed), &choices, count, &mut result).unwrap();
+        String::from_utf8(result)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parsing() {
+        let choices = read_choices(CHOICES.as_bytes()).unwrap();
+        assert_eq!(choices, [&b"alice"[..], b"bob", b"carol", b"dave"]);
+        assert!(read_choices(&b"\n\n"[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reproducible() {
+        let result = picks("abc", 200);
+        assert_eq!(result.len(), 200);
+        assert_eq!(result, picks("abc", 200));
+        assert_ne!(result, picks("abd", 200));
+        assert!(result
+            .iter()
+            .all(|p| ["alice", "bob", "carol", "dave"].contains(&p.as_str())));
+
+        // With enough picks, every choice shows up
+        for c in ["alice", "bob", "carol", "dave"] {
+            assert!(result.iter().any(|p| p == c));
+        }
+    }
+}
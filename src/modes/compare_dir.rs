@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::compare_stream::{compare_stream, StreamComparison};
+use crate::stream::{Chunks, DataSource};
+
+/// The outcome of verifying one of the files of a split output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileCheck {
+    /// The file is the expected part of the stream, where the comparison is of the expected part with the file
+    Compared(StreamComparison),
+    /// The file is expected from the length of the output, but is not in the directory
+    Missing,
+    /// The file is in the directory, but the output has ended before it
+    Unexpected,
+}
+
+impl FileCheck {
+    pub fn passed(&self) -> bool {
+        matches!(self, FileCheck::Compared(StreamComparison::Match { .. }))
+    }
+}
+
+impl std::fmt::Display for FileCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileCheck::Compared(StreamComparison::Match { length }) => {
+                write!(f, "pass ({length} bytes)")
+            }
+            FileCheck::Compared(StreamComparison::Mismatch { offset }) => {
+                write!(f, "FAIL, differs at offset {offset} of the file")
+            }
+            FileCheck::Compared(StreamComparison::ReferenceTooShort { length }) => {
+                write!(f, "FAIL, too short, ends after {length} bytes")
+            }
+            FileCheck::Compared(StreamComparison::ReferenceTooLong { length }) => {
+                write!(f, "FAIL, too long, has more than {length} bytes")
+            }
+            FileCheck::Missing => write!(f, "FAIL, missing"),
+            FileCheck::Unexpected => write!(f, "FAIL, unexpected after the end of the output"),
+        }
+    }
+}
+
+/// The files of a split output in a directory, which are its regular files, in the order of their names
+pub fn split_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Verify the files of an output that was split into files of `split_size` bytes, in order, where only the
+/// last file may be shorter. If the length of the whole output is given, the number of files and the length of
+/// the last file are checked against it, and the check of every file that is expected but not given is
+/// `FileCheck::Missing`. Otherwise, the files given are the whole output.
+///
+/// The stream is generated once, file after file, so a file that fails does not affect the checks of the others.
+pub fn compare_dir(
+    writer: InfiniteDataWriter,
+    files: &[PathBuf],
+    split_size: u64,
+    length: Option<u64>,
+) -> std::io::Result<Vec<(Option<PathBuf>, FileCheck)>> {
+    assert!(split_size > 0, "The split size must be positive");
+
+    let expected_count = match length {
+        Some(length) => length.div_ceil(split_size),
+        None => files.len() as u64,
+    };
+    let mut stream = Chunks::new(writer.map(|batch| batch.to_vec()));
+    let mut checks = Vec::new();
+
+    for index in 0..expected_count.max(files.len() as u64) {
+        let file = files.get(index as usize);
+        if index >= expected_count {
+            checks.push((file.cloned(), FileCheck::Unexpected));
+            continue;
+        }
+
+        let offset = index * split_size;
+        let is_last = index + 1 == expected_count;
+        let expected_length = match length {
+            Some(length) => split_size.min(length - offset),
+            None => split_size,
+        };
+        let mut part = Part {
+            stream: &mut stream,
+            remaining: expected_length,
+        };
+
+        let check = match file {
+            Some(path) => {
+                let comparison = compare_stream(&mut part, std::fs::File::open(path)?)?;
+                match comparison {
+                    // Without the length of the output, the last file can have any length up to the split size
+                    StreamComparison::ReferenceTooShort { length: read }
+                        if length.is_none() && is_last && read > 0 =>
+                    {
+                        FileCheck::Compared(StreamComparison::Match { length: read })
+                    }
+                    comparison => FileCheck::Compared(comparison),
+                }
+            }
+            None => FileCheck::Missing,
+        };
+        part.skip_rest();
+        checks.push((file.cloned(), check));
+    }
+    Ok(checks)
+}
+
+/// The next bytes of a stream that are expected in one file
+struct Part<'a, S> {
+    stream: &'a mut S,
+    remaining: u64,
+}
+
+impl<S: DataSource> Part<'_, S> {
+    /// Consume the bytes of the part that were not compared, so that the stream is at the start of the next part
+    fn skip_rest(&mut self) {
+        let mut buffer = vec![0; crate::stream::GENERATION_BUFFER_SIZE];
+        while self.fill(&mut buffer) > 0 {}
+    }
+}
+
+impl<S: DataSource> DataSource for &mut Part<'_, S> {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        (**self).fill(buffer)
+    }
+}
+
+impl<S: DataSource> DataSource for Part<'_, S> {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        let length = self.remaining.min(buffer.len() as u64) as usize;
+        if length == 0 {
+            return 0;
+        }
+        let filled = self.stream.fill(&mut buffer[..length]);
+        self.remaining -= filled as u64;
+        filled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_split(dir: &Path, length: u64, split_size: u64) {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(length))
+            .flatten()
+            .collect();
+        for (i, part) in data.chunks(split_size as usize).enumerate() {
+            std::fs::write(dir.join(format!("part{i:03}")), part).unwrap();
+        }
+    }
+
+    fn checks(dir: &Path, length: Option<u64>) -> Vec<FileCheck> {
+        let files = split_files(dir).unwrap();
+        compare_dir(InfiniteDataWriter::new("abc"), &files, 1000, length)
+            .unwrap()
+            .into_iter()
+            .map(|(_, check)| check)
+            .collect()
+    }
+
+    #[test]
+    fn split_files_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        write_split(dir.path(), 4500, 1000);
+
+        for length in [Some(4500), None] {
+            let checks = checks(dir.path(), length);
+            assert_eq!(checks.len(), 5);
+            assert!(checks.iter().all(FileCheck::passed), "{checks:?}");
+        }
+    }
+
+    #[test]
+    fn tampered_file_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        write_split(dir.path(), 4500, 1000);
+        let tampered = dir.path().join("part002");
+        let mut data = std::fs::read(&tampered).unwrap();
+        data[123] ^= 1;
+        std::fs::write(&tampered, data).unwrap();
+
+        let checks = checks(dir.path(), Some(4500));
+        assert_eq!(
+            checks[2],
+            FileCheck::Compared(StreamComparison::Mismatch { offset: 123 })
+        );
+        assert!([0, 1, 3, 4].iter().all(|&i| checks[i].passed()));
+    }
+
+    #[test]
+    fn missing_and_short_last_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_split(dir.path(), 4500, 1000);
+        assert_eq!(
+            checks(dir.path(), Some(5000))[4],
+            FileCheck::Compared(StreamComparison::ReferenceTooShort { length: 500 })
+        );
+        assert_eq!(checks(dir.path(), Some(6000))[5], FileCheck::Missing);
+        assert_eq!(checks(dir.path(), Some(3500))[4], FileCheck::Unexpected);
+
+        std::fs::remove_file(dir.path().join("part004")).unwrap();
+        assert_eq!(checks(dir.path(), Some(4500))[4], FileCheck::Missing);
+        assert!(checks(dir.path(), None).iter().all(FileCheck::passed));
+    }
+}
@@ -0,0 +1,148 @@
+use std::io::Read;
+
+use blake2::{Blake2b512, Digest};
+
+use crate::stream::{DataSource, GENERATION_BUFFER_SIZE};
+
+/// Compare `data` with the generated stream window by window, where every window of `window_size` bytes
+/// is compared by its Blake2b hash, and return the indices of the windows that differ. The windows cover
+/// the data, and the last window may be shorter. The windows are hashed as the data is read,
+/// so the memory used does not depend on the size of the data.
+pub fn differing_windows(
+    generated: impl DataSource,
+    mut data: impl Read,
+    window_size: u64,
+) -> std::io::Result<Vec<u64>> {
+    let mut generated = GeneratedBytes::new(generated);
+    let mut data_hasher = Blake2b512::new();
+    let mut generated_hasher = Blake2b512::new();
+    let mut in_window = 0;
+    let mut window_index = 0;
+    let mut differing = Vec::new();
+
+    let mut finish_window = |data_hasher: &mut Blake2b512, generated_hasher: &mut Blake2b512| {
+        if data_hasher.finalize_reset() != generated_hasher.finalize_reset() {
+            differing.push(window_index);
+        }
+        window_index += 1;
+    };
+
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = match data.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        let mut chunk = &buffer[..read];
+        while !chunk.is_empty() {
+            let taken = (window_size - in_window).min(chunk.len() as u64) as usize;
+            data_hasher.update(&chunk[..taken]);
+            generated.hash_next(taken, &mut generated_hasher);
+            chunk = &chunk[taken..];
+
+            in_window += taken as u64;
+            if in_window == window_size {
+                finish_window(&mut data_hasher, &mut generated_hasher);
+                in_window = 0;
+            }
+        }
+    }
+
+    if in_window > 0 {
+        finish_window(&mut data_hasher, &mut generated_hasher);
+    }
+    Ok(differing)
+}
+
+/// The generated stream, consumed in arbitrary lengths
+struct GeneratedBytes<S> {
+    source: S,
+    buffer: Vec<u8>,
+    start: usize,
+    end: usize,
+}
+
+impl<S: DataSource> GeneratedBytes<S> {
+    fn new(source: S) -> Self {
+        Self {
+            source,
+            buffer: vec![0; GENERATION_BUFFER_SIZE],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Feed the next `length` generated bytes to the hasher, or fewer if the generated stream ends
+    fn hash_next(&mut self, mut length: usize, hasher: &mut Blake2b512) {
+        while length > 0 {
+            if self.start == self.end {
+                self.start = 0;
+                self.end = self.source.fill(&mut self.buffer);
+                if self.end == 0 {
+                    return;
+                }
+            }
+            let taken = length.min(self.end - self.start);
+            hasher.update(&self.buffer[self.start..self.start + taken]);
+            self.start += taken;
+            length -= taken;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::{CounterDataWriter, FiniteDataWriter};
+
+    use super::*;
+
+    fn generated(length: u64) -> Vec<u8> {
+        FiniteDataWriter::new("abc", Some(length))
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn corrupted_window_is_flagged() {
+        let mut data = generated(1000);
+        let windows =
+            |data: &[u8]| differing_windows(FiniteDataWriter::new("abc", None), data, 100).unwrap();
+        assert!(windows(&data).is_empty());
+
+        data[350] ^= 1;
+        assert_eq!(windows(&data), [3]);
+
+        data[999] ^= 1;
+        assert_eq!(windows(&data), [3, 9]);
+    }
+
+    #[test]
+    fn partial_last_window() {
+        let data = generated(250);
+        assert!(
+            differing_windows(FiniteDataWriter::new("abc", None), &data[..], 100)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            differing_windows(FiniteDataWriter::new("abd", None), &data[..], 100).unwrap(),
+            [0, 1, 2]
+        );
+        assert!(
+            differing_windows(FiniteDataWriter::new("abc", None), &[][..], 100)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn other_generator() {
+        let data: Vec<u8> = CounterDataWriter::new("abc", Some(250)).flatten().collect();
+        let windows = |generated| differing_windows(generated, &data[..], 100).unwrap();
+        assert!(windows(CounterDataWriter::new("abc", None)).is_empty());
+        assert_eq!(windows(CounterDataWriter::new("abc", Some(150))), [1, 2]);
+    }
+}
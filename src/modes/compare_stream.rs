@@ -0,0 +1,183 @@
+use std::io::Read;
+
+use crate::{
+    exit_codes,
+    stream::{DataSource, GENERATION_BUFFER_SIZE},
+};
+
+/// The result of comparing a reference stream with the generated data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamComparison {
+    /// Both have the given length, and the same bytes
+    Match { length: u64 },
+    /// The first differing byte is at the given offset
+    Mismatch { offset: u64 },
+    /// The reference matches the generated data, but ends after the given length
+    ReferenceTooShort { length: u64 },
+    /// The reference matches the generated data, but has more bytes after it
+    ReferenceTooLong { length: u64 },
+}
+
+impl StreamComparison {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            StreamComparison::Match { .. } => exit_codes::SUCCESS,
+            _ => exit_codes::MISMATCH,
+        }
+    }
+}
+
+/// Compare the reference with the generated data, chunk by chunk, as they are generated and read,
+/// so that neither of them is held in memory as a whole.
+pub fn compare_stream(
+    generated: impl DataSource,
+    mut reference: impl Read,
+) -> std::io::Result<StreamComparison> {
+    let [comparison] = compare_streams(generated, [&mut reference as &mut dyn Read])?;
+    Ok(comparison)
+}
+
+/// Same as `compare_stream`, but with several references, which are read in lockstep with the generated
+/// data, in a single pass, so that a source and its copies can be checked together. Every reference
+/// is read until its comparison is decided, and the comparisons are in the order of the references.
+pub fn compare_streams<const N: usize>(
+    mut generated: impl DataSource,
+    mut references: [&mut dyn Read; N],
+) -> std::io::Result<[StreamComparison; N]> {
+    let mut offset = 0;
+    let mut generated_buffer = vec![0; GENERATION_BUFFER_SIZE];
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+    let mut comparisons = [None; N];
+
+    while comparisons.iter().any(Option::is_none) {
+        let length = generated.fill(&mut generated_buffer);
+        if length == 0 {
+            break;
+        }
+        let chunk = &generated_buffer[..length];
+
+        for (reference, comparison) in references.iter_mut().zip(&mut comparisons) {
+            if comparison.is_some() {
+                continue;
+            }
+            let read = read_up_to(reference, &mut buffer[..length])?;
+            if let Some(i) = chunk.iter().zip(&buffer[..read]).position(|(a, b)| a != b) {
+                *comparison = Some(StreamComparison::Mismatch {
+                    offset: offset + i as u64,
+                });
+            } else if read < chunk.len() {
+                *comparison = Some(StreamComparison::ReferenceTooShort {
+                    length: offset + read as u64,
+                });
+            }
+        }
+        offset += length as u64;
+    }
+
+    for (reference, comparison) in references.iter_mut().zip(&mut comparisons) {
+        if comparison.is_none() {
+            *comparison = Some(match read_up_to(reference, &mut [0])? {
+                0 => StreamComparison::Match { length: offset },
+                _ => StreamComparison::ReferenceTooLong { length: offset },
+            });
+        }
+    }
+    Ok(comparisons.map(|c| c.expect("Every comparison is decided")))
+}
+
+/// Fill the buffer as far as the reader allows, returning the number of bytes read,
+/// which is only smaller than the buffer at the end of the reader
+fn read_up_to(reader: &mut (impl Read + ?Sized), buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::FiniteDataWriter;
+
+    use super::*;
+
+    fn compare(reference: &[u8]) -> StreamComparison {
+        compare_stream(FiniteDataWriter::new("abc", Some(10_000)), reference).unwrap()
+    }
+
+    fn reference() -> Vec<u8> {
+        FiniteDataWriter::new("abc", Some(10_000))
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn same_stream() {
+        let outcome = compare(&reference());
+        assert_eq!(outcome, StreamComparison::Match { length: 10_000 });
+        assert_eq!(outcome.exit_code(), exit_codes::SUCCESS);
+    }
+
+    #[test]
+    fn altered_byte() {
+        for offset in [0, 63, 64, 1234, 9_999] {
+            let mut altered = reference();
+            altered[offset] ^= 0x80;
+            let outcome = compare(&altered);
+            assert_eq!(
+                outcome,
+                StreamComparison::Mismatch {
+                    offset: offset as u64
+                }
+            );
+            assert_eq!(outcome.exit_code(), exit_codes::MISMATCH);
+        }
+    }
+
+    #[test]
+    fn source_and_copy() {
+        let source = reference();
+        let compare = |file: &[u8], copy: &[u8]| {
+            compare_streams(
+                FiniteDataWriter::new("abc", Some(10_000)),
+                [&mut &file[..] as &mut dyn Read, &mut &copy[..]],
+            )
+            .unwrap()
+        };
+
+        let matching = StreamComparison::Match { length: 10_000 };
+        assert_eq!(compare(&source, &source), [matching, matching]);
+
+        let mut corrupted = source.clone();
+        corrupted[4321] ^= 1;
+        assert_eq!(
+            compare(&source, &corrupted),
+            [matching, StreamComparison::Mismatch { offset: 4321 }]
+        );
+        assert_eq!(
+            compare(&corrupted, &source[..9000]),
+            [
+                StreamComparison::Mismatch { offset: 4321 },
+                StreamComparison::ReferenceTooShort { length: 9000 }
+            ]
+        );
+    }
+
+    #[test]
+    fn different_lengths() {
+        let reference = reference();
+        assert_eq!(
+            compare(&reference[..5000]),
+            StreamComparison::ReferenceTooShort { length: 5000 }
+        );
+        assert_eq!(
+            compare(&[&reference[..], &[0]].concat()),
+            StreamComparison::ReferenceTooLong { length: 10_000 }
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeSet;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::uniform_below;
+
+/// Pick `count` distinct bit positions in `0..total_bits` uniformly, using the generated stream, with
+/// Robert Floyd's algorithm, which takes exactly `count` picks. The positions are returned in order.
+pub fn pick_bit_positions(writer: InfiniteDataWriter, total_bits: u64, count: u64) -> Vec<u64> {
+    assert!(count <= total_bits);
+
+    let mut bytes = writer.flatten();
+    let mut positions = BTreeSet::new();
+    for j in total_bits - count..total_bits {
+        let candidate = uniform_below(&mut bytes, j + 1);
+        if !positions.insert(candidate) {
+            positions.insert(j);
+        }
+    }
+    positions.into_iter().collect()
+}
+
+/// Flip `count` distinct bits of the data, at positions picked with the generated stream, where bit `i`
+/// is bit `i % 8` (from the least significant) of byte `i / 8`. The same seed flips the same bits.
+pub fn corrupt(writer: InfiniteDataWriter, data: &mut [u8], count: u64) -> anyhow::Result<()> {
+    let total_bits = data.len() as u64 * 8;
+    if count > total_bits {
+        return Err(anyhow::anyhow!(
+            "Cannot flip {count} distinct bits of data with only {total_bits} bits"
+        ));
+    }
+
+    for position in pick_bit_positions(writer, total_bits, count) {
+        data[(position / 8) as usize] ^= 1 << (position % 8);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corrupted(seed: &str, data: &[u8], count: u64) -> Vec<u8> {
+        let mut result = data.to_vec();
+        corrupt(InfiniteDataWriter::new(seed), &mut result, count).unwrap();
+        result
+    }
+
+    fn changed_bits(a: &[u8], b: &[u8]) -> u32 {
+        a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+    }
+
+    #[test]
+    fn flips_count_distinct_bits() {
+        let data = vec![0x5a; 1000];
+        for count in [0, 1, 10, 4000, 8000] {
+            let result = corrupted("abc", &data, count);
+            assert_eq!(changed_bits(&data, &result), count as u32);
+        }
+    }
+
+    #[test]
+    fn reproducible() {
+        let data = vec![0; 1000];
+        let result = corrupted("abc", &data, 50);
+        assert_eq!(result, corrupted("abc", &data, 50));
+        assert_ne!(result, corrupted("abd", &data, 50));
+
+        let positions = pick_bit_positions(InfiniteDataWriter::new("abc"), 8000, 50);
+        assert_eq!(positions.len(), 50);
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        for position in positions {
+            assert_eq!(result[(position / 8) as usize] >> (position % 8) & 1, 1);
+        }
+    }
+
+    #[test]
+    fn too_many_bits() {
+        let mut data = vec![0; 2];
+        assert!(corrupt(InfiniteDataWriter::new("abc"), &mut data, 17).is_err());
+        assert!(corrupt(InfiniteDataWriter::new("abc"), &mut data, 16).is_ok());
+        assert_eq!(data, [0xff, 0xff]);
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::uniform_below;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A range of UTC times, in seconds since the Unix epoch, that includes its start and excludes its end.
+/// It is given as `START..END`, where both are RFC 3339 times in UTC (`2024-01-01T00:00:00Z`), or dates
+/// (`2024-01-01`), which are the start of the day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl std::str::FromStr for DateRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| format!("Expected START..END, found: {s}"))?;
+        let (start, end) = (parse_timestamp(start)?, parse_timestamp(end)?);
+        if start >= end {
+            return Err(format!("The range is empty: {s}"));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+/// Parse a time in UTC, as `YYYY-MM-DDTHH:MM:SSZ` or `YYYY-MM-DD`, into seconds since the Unix epoch
+pub fn parse_timestamp(s: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid time, expected YYYY-MM-DDTHH:MM:SSZ or YYYY-MM-DD: {s}");
+    let number = |digits: &str| -> Result<i64, String> {
+        match digits.bytes().all(|b| b.is_ascii_digit()) {
+            true => digits.parse().map_err(|_| invalid()),
+            false => Err(invalid()),
+        }
+    };
+
+    let (date, time) = match s.split_once('T') {
+        Some((date, time)) => (date, Some(time.strip_suffix('Z').ok_or_else(invalid)?)),
+        None => (s, None),
+    };
+    let date: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = date[..] else {
+        return Err(invalid());
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err(invalid());
+    }
+    let (year, month, day) = (number(year)?, number(month)?, number(day)?);
+
+    let days = days_from_civil(year, month, day);
+    if !(1..=12).contains(&month) || civil_from_days(days) != (year, month, day) {
+        return Err(invalid());
+    }
+
+    let seconds = match time {
+        Some(time) => {
+            let time: Vec<&str> = time.split(':').collect();
+            let [hours, minutes, seconds] = time[..] else {
+                return Err(invalid());
+            };
+            if [hours, minutes, seconds].iter().any(|t| t.len() != 2) {
+                return Err(invalid());
+            }
+            let (hours, minutes, seconds) = (number(hours)?, number(minutes)?, number(seconds)?);
+            if hours > 23 || minutes > 59 || seconds > 59 {
+                return Err(invalid());
+            }
+            hours * 3600 + minutes * 60 + seconds
+        }
+        None => 0,
+    };
+    Ok(days * SECONDS_PER_DAY + seconds)
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 time in UTC, `YYYY-MM-DDTHH:MM:SSZ`
+pub fn format_timestamp(timestamp: i64) -> String {
+    let (days, seconds) = (
+        timestamp.div_euclid(SECONDS_PER_DAY),
+        timestamp.rem_euclid(SECONDS_PER_DAY),
+    );
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+/// The number of days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date of the proleptic Gregorian calendar of a number of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Write `count` times, one per line, each picked uniformly with a resolution of a second in the range,
+/// from the generated stream, and formatted as RFC 3339 times in UTC.
+pub fn write_datetimes(
+    writer: InfiniteDataWriter,
+    count: u64,
+    range: DateRange,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+    let span = range.end.abs_diff(range.start);
+    for _ in 0..count {
+        let timestamp = range.start + uniform_below(&mut bytes, span) as i64;
+        writeln!(out, "{}", format_timestamp(timestamp))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetimes(seed: &str, count: u64, range: &str) -> String {
+        let mut out = Vec::new();
+        write_datetimes(
+            InfiniteDataWriter::new(seed),
+            count,
+            range.parse().unwrap(),
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn formatting_and_parsing() {
+        for (text, timestamp) in [
+            ("1970-01-01T00:00:00Z", 0),
+            ("2000-02-29T12:34:56Z", 951_827_696),
+            ("1969-12-31T23:59:59Z", -1),
+            ("9999-12-31T23:59:59Z", 253_402_300_799),
+        ] {
+            assert_eq!(parse_timestamp(text).unwrap(), timestamp, "{text}");
+            assert_eq!(format_timestamp(timestamp), text);
+        }
+        assert_eq!(parse_timestamp("2024-03-01").unwrap(), 1_709_251_200);
+
+        for invalid in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-1-01",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:00:00",
+            "2024-01-01T00:00:00+01:00",
+            "+024-01-01",
+        ] {
+            assert!(parse_timestamp(invalid).is_err(), "{invalid}");
+        }
+        assert!("2024-01-01..2024-01-01".parse::<DateRange>().is_err());
+        assert!("2024-01-01".parse::<DateRange>().is_err());
+    }
+
+    #[test]
+    fn within_range_and_stable() {
+        let range = "2024-01-01T00:00:00Z..2024-01-02";
+        let text = datetimes("abc", 1000, range);
+        assert_eq!(text, datetimes("abc", 1000, range));
+        assert_ne!(text, datetimes("abd", 1000, range));
+        assert_eq!(
+            datetimes("abc", 3, range),
+            "2024-01-01T00:50:02Z\n2024-01-01T21:23:54Z\n2024-01-01T12:08:44Z\n"
+        );
+
+        let DateRange { start, end } = range.parse().unwrap();
+        for line in text.lines() {
+            let timestamp = parse_timestamp(line).unwrap();
+            assert!((start..end).contains(&timestamp), "{line}");
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+/// Write the first `count` internal states of the hash chain, one per line, hex-encoded.
+/// The first state is the hash of the seed, and every following state is the hash of the previous one.
+pub fn write_states(
+    mut writer: InfiniteDataWriter,
+    count: u64,
+    mut out: impl Write,
+) -> std::io::Result<()> {
+    let mut batch = [0; 64];
+    for _ in 0..count {
+        writeln!(out, "{}", hex::encode(writer.seed()))?;
+        writer.pull_into(&mut batch);
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_state_is_seed_hash() {
+        let mut out = Vec::new();
+        write_states(InfiniteDataWriter::new("abc"), 3, &mut out).unwrap();
+        let lines: Vec<_> = std::str::from_utf8(&out).unwrap().lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923");
+
+        // The states are the batches of the generated stream
+        let mut writer = InfiniteDataWriter::new("abc");
+        for line in lines {
+            assert_eq!(line, hex::encode(writer.pull()));
+        }
+    }
+}
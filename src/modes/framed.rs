@@ -0,0 +1,62 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use crate::stream::{take_bytes, Chunks, DataSource};
+
+/// Write `length` bytes of the generated stream as frames, where every frame is the length of its record
+/// as a little-endian u32, followed by the record. The records have `record_size` bytes, except for the last,
+/// which may be shorter, and they are consecutive parts of the stream.
+pub fn write_frames(
+    writer: InfiniteDataWriter,
+    length: u64,
+    record_size: u32,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut data = Chunks::new(take_bytes(writer.map(|batch| batch.to_vec()), length));
+    let mut record = vec![0; record_size as usize];
+
+    loop {
+        let record_length = data.fill(&mut record);
+        if record_length == 0 {
+            return Ok(());
+        }
+        out.write_all(&(record_length as u32).to_le_bytes())?;
+        out.write_all(&record[..record_length])?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::FiniteDataWriter;
+
+    use super::*;
+
+    #[test]
+    fn frames_hold_the_stream() {
+        let mut out = Vec::new();
+        write_frames(InfiniteDataWriter::new("abc"), 1000, 300, &mut out).unwrap();
+
+        let mut records = Vec::new();
+        let mut frames = &out[..];
+        while !frames.is_empty() {
+            let (length, rest) = frames.split_at(4);
+            let length = u32::from_le_bytes(length.try_into().unwrap()) as usize;
+            let (record, rest) = rest.split_at(length);
+            records.push(record.to_vec());
+            frames = rest;
+        }
+
+        let lengths: Vec<usize> = records.iter().map(Vec::len).collect();
+        assert_eq!(lengths, [300, 300, 300, 100]);
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+        assert_eq!(records.concat(), expected);
+    }
+
+    #[test]
+    fn empty_stream_has_no_frames() {
+        let mut out = Vec::new();
+        write_frames(InfiniteDataWriter::new("abc"), 0, 300, &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::uniform_below;
+use crate::stream::{Chunks, DataSource, GENERATION_BUFFER_SIZE};
+
+/// The unit of the lengths of the runs
+pub const BLOCK_SIZE: u64 = 4096;
+
+/// The largest number of blocks of a run
+const MAX_BLOCKS_PER_RUN: u64 = 16;
+
+/// What the layout writer is forked with, so that the layout is independent of the data
+const LAYOUT_CONTEXT: &[u8] = b"rdgen fs-image layout";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    Data,
+    Hole,
+}
+
+/// A run of the image, which is either data or a hole
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub kind: RunKind,
+    pub length: u64,
+}
+
+/// The runs of an image of `total` bytes, which alternate between data and holes, starting with data.
+/// Every run has 1 to 16 blocks of 4096 bytes, picked uniformly, and the last run is truncated to the total.
+///
+/// The lengths are picked from a fork of the generator (its seed mixed with a fixed context),
+/// so that the data runs, put together, are the generated stream itself.
+pub fn layout(writer: &InfiniteDataWriter, total: u64) -> impl Iterator<Item = Run> {
+    let mut layout_writer = InfiniteDataWriter::from_seed_bytes(*writer.seed());
+    layout_writer.mix_in(LAYOUT_CONTEXT);
+    let mut bytes = layout_writer.flatten();
+
+    let mut offset = 0;
+    let mut kind = RunKind::Data;
+    std::iter::from_fn(move || {
+        if offset == total {
+            return None;
+        }
+        let blocks = uniform_below(&mut bytes, MAX_BLOCKS_PER_RUN) + 1;
+        let length = (blocks * BLOCK_SIZE).min(total - offset);
+        let run = Run { kind, length };
+
+        offset += length;
+        kind = match kind {
+            RunKind::Data => RunKind::Hole,
+            RunKind::Hole => RunKind::Data,
+        };
+        Some(run)
+    })
+}
+
+/// Write an image of `total` bytes, with the runs of `layout`, where the data runs are filled with the
+/// generated stream, and every hole is skipped with `skip_hole`.
+pub fn write_image<W: Write>(
+    writer: InfiniteDataWriter,
+    total: u64,
+    out: &mut W,
+    mut skip_hole: impl FnMut(&mut W, u64) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let runs = layout(&writer, total);
+    let mut data = Chunks::new(writer.map(|batch| batch.to_vec()));
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+
+    for run in runs {
+        match run.kind {
+            RunKind::Data => {
+                let mut remaining = run.length;
+                while remaining > 0 {
+                    let length = remaining.min(buffer.len() as u64) as usize;
+                    data.fill(&mut buffer[..length]);
+                    out.write_all(&buffer[..length])?;
+                    remaining -= length as u64;
+                }
+            }
+            RunKind::Hole => skip_hole(out, run.length)?,
+        }
+    }
+    Ok(())
+}
+
+/// Skip a hole by seeking over it, which leaves a real hole in files on filesystems that support them.
+/// Seeking does not extend the file, so if the image ends with a hole, the length of the file must be set
+/// to the total after writing the image.
+pub fn seek_hole<W: Write + Seek>(out: &mut W, length: u64) -> std::io::Result<()> {
+    out.seek(SeekFrom::Current(length as i64)).map(|_| ())
+}
+
+/// Skip a hole by writing zeros, for outputs that cannot seek
+pub fn zero_hole<W: Write>(out: &mut W, length: u64) -> std::io::Result<()> {
+    std::io::copy(&mut std::io::repeat(0).take(length), out).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible_layout() {
+        let total = 1_000_000;
+        let runs: Vec<Run> = layout(&InfiniteDataWriter::new("abc"), total).collect();
+        assert_eq!(
+            runs,
+            layout(&InfiniteDataWriter::new("abc"), total).collect::<Vec<_>>()
+        );
+        assert_ne!(
+            runs,
+            layout(&InfiniteDataWriter::new("abd"), total).collect::<Vec<_>>()
+        );
+
+        assert_eq!(runs.iter().map(|r| r.length).sum::<u64>(), total);
+        for (i, run) in runs.iter().enumerate() {
+            let expected_kind = if i % 2 == 0 {
+                RunKind::Data
+            } else {
+                RunKind::Hole
+            };
+            assert_eq!(run.kind, expected_kind);
+            assert!(run.length <= MAX_BLOCKS_PER_RUN * BLOCK_SIZE);
+            if i < runs.len() - 1 {
+                assert_eq!(run.length % BLOCK_SIZE, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn data_runs_are_the_stream() {
+        let total = 500_000;
+        let runs: Vec<Run> = layout(&InfiniteDataWriter::new("abc"), total).collect();
+
+        let mut seeking = std::io::Cursor::new(Vec::new());
+        write_image(
+            InfiniteDataWriter::new("abc"),
+            total,
+            &mut seeking,
+            seek_hole,
+        )
+        .unwrap();
+        let mut image = seeking.into_inner();
+        image.resize(total as usize, 0);
+        let mut zeros = Vec::new();
+        write_image(InfiniteDataWriter::new("abc"), total, &mut zeros, zero_hole).unwrap();
+        assert_eq!(image, zeros);
+        assert_eq!(image.len() as u64, total);
+
+        let mut data = Vec::new();
+        let mut offset = 0;
+        for run in runs {
+            let range = offset..offset + run.length as usize;
+            match run.kind {
+                RunKind::Data => data.extend_from_slice(&image[range]),
+                RunKind::Hole => assert!(image[range].iter().all(|&b| b == 0)),
+            }
+            offset += run.length as usize;
+        }
+        let expected: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(data.len() as u64))
+            .flatten()
+            .collect();
+        assert_eq!(data, expected);
+    }
+}
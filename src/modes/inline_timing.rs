@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::uniform_below;
+use crate::stream::{take_bytes, Chunks, DataSource};
+
+/// What the delay writer is forked with, so that the delays are independent of the data
+const DELAY_CONTEXT: &[u8] = b"rdgen inline-timing delays";
+
+/// Write `length` bytes of the generated stream as timed frames, where every frame is a delay in milliseconds
+/// as a little-endian u32, then the length of its record as a little-endian u32, followed by the record.
+/// The records are as in `framed::write_frames`, and every delay is picked uniformly from 0 to `max_delay`.
+///
+/// The delays are picked from a fork of the generator (its seed mixed with a fixed context),
+/// so that the records, put together, are the generated stream itself.
+pub fn write_timed_frames(
+    writer: InfiniteDataWriter,
+    length: u64,
+    record_size: u32,
+    max_delay: u32,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut delay_writer = InfiniteDataWriter::from_seed_bytes(*writer.seed());
+    delay_writer.mix_in(DELAY_CONTEXT);
+    let mut delay_bytes = delay_writer.flatten();
+
+    let mut data = Chunks::new(take_bytes(writer.map(|batch| batch.to_vec()), length));
+    let mut record = vec![0; record_size as usize];
+
+    loop {
+        let record_length = data.fill(&mut record);
+        if record_length == 0 {
+            return Ok(());
+        }
+        let delay = uniform_below(&mut delay_bytes, u64::from(max_delay) + 1) as u32;
+        out.write_all(&delay.to_le_bytes())?;
+        out.write_all(&(record_length as u32).to_le_bytes())?;
+        out.write_all(&record[..record_length])?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::FiniteDataWriter;
+
+    use super::*;
+
+    /// The delays and the records of the frames
+    fn parse(mut frames: &[u8]) -> (Vec<u32>, Vec<Vec<u8>>) {
+        let (mut delays, mut records) = (Vec::new(), Vec::new());
+        while !frames.is_empty() {
+            let (header, rest) = frames.split_at(8);
+            delays.push(u32::from_le_bytes(header[..4].try_into().unwrap()));
+            let length = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+            let (record, rest) = rest.split_at(length);
+            records.push(record.to_vec());
+            frames = rest;
+        }
+        (delays, records)
+    }
+
+    #[test]
+    fn frames_hold_the_stream_and_delays() {
+        let mut out = Vec::new();
+        write_timed_frames(InfiniteDataWriter::new("abc"), 10_000, 30, 250, &mut out).unwrap();
+
+        let (delays, records) = parse(&out);
+        assert_eq!(records.len(), 334);
+        assert!(records[..333].iter().all(|r| r.len() == 30));
+        assert_eq!(records[333].len(), 10);
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(10_000))
+            .flatten()
+            .collect();
+        assert_eq!(records.concat(), expected);
+
+        assert!(delays.iter().all(|&d| d <= 250));
+        assert!(delays.iter().any(|&d| d < 50) && delays.iter().any(|&d| d > 200));
+
+        let mut again = Vec::new();
+        write_timed_frames(InfiniteDataWriter::new("abc"), 10_000, 30, 250, &mut again).unwrap();
+        assert_eq!(again, out);
+    }
+
+    #[test]
+    fn zero_max_delay() {
+        let mut out = Vec::new();
+        write_timed_frames(InfiniteDataWriter::new("abc"), 100, 64, 0, &mut out).unwrap();
+        let (delays, records) = parse(&out);
+        assert_eq!(delays, [0, 0]);
+        assert_eq!(records.concat().len(), 100);
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use rdgen_lib::InfiniteDataWriter;
+
+/// Write `count` IP addresses, one per line, optionally followed by a port.
+///
+/// Every IPv4 address is made of the next 4 bytes of the generated stream, in order, as its octets,
+/// and every IPv6 address of the next 16 bytes, in network (big-endian) order. When `with_port` is set,
+/// the 2 bytes after the address are the port, in big-endian; IPv6 addresses are then written in brackets.
+/// All addresses are possible, including reserved ones.
+pub fn write_ips(
+    writer: InfiniteDataWriter,
+    count: u64,
+    ipv6: bool,
+    with_port: bool,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+
+    for _ in 0..count {
+        let address: IpAddr = if ipv6 {
+            Ipv6Addr::from(next_bytes::<16>(&mut bytes)).into()
+        } else {
+            Ipv4Addr::from(next_bytes::<4>(&mut bytes)).into()
+        };
+
+        if with_port {
+            let port = u16::from_be_bytes(next_bytes(&mut bytes));
+            writeln!(out, "{}", SocketAddr::new(address, port))?;
+        } else {
+            writeln!(out, "{address}")?;
+        }
+    }
+    Ok(())
+}
+
+fn next_bytes<const N: usize>(bytes: &mut impl Iterator<Item = u8>) -> [u8; N] {
+    std::array::from_fn(|_| bytes.next().expect("The stream is infinite"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ips(seed: &str, count: u64, ipv6: bool, with_port: bool) -> String {
+        let mut out = Vec::new();
+        write_ips(
+            InfiniteDataWriter::new(seed),
+            count,
+            ipv6,
+            with_port,
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn stable_ipv4_addresses() {
+        let text = ips("abc", 100, false, false);
+        let addresses: Vec<Ipv4Addr> = text.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(addresses.len(), 100);
+
+        let data: Vec<u8> = InfiniteDataWriter::new("abc").flatten().take(400).collect();
+        for (address, octets) in addresses.iter().zip(data.chunks(4)) {
+            assert_eq!(address.octets(), octets);
+        }
+
+        assert_eq!(text, ips("abc", 100, false, false));
+        assert_ne!(text, ips("abd", 100, false, false));
+    }
+
+    #[test]
+    fn ipv6_and_ports() {
+        let data: Vec<u8> = InfiniteDataWriter::new("abc").flatten().take(36).collect();
+
+        let text = ips("abc", 2, true, false);
+        let addresses: Vec<Ipv6Addr> = text.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(addresses[0].octets(), data[..16]);
+        assert_eq!(addresses[1].octets(), data[16..32]);
+
+        let text = ips("abc", 2, true, true);
+        let addresses: Vec<SocketAddr> = text.lines().map(|l| l.parse().unwrap()).collect();
+        assert_eq!(
+            addresses[0].ip(),
+            IpAddr::from(<[u8; 16]>::try_from(&data[..16]).unwrap())
+        );
+        assert_eq!(
+            addresses[0].port(),
+            u16::from_be_bytes([data[16], data[17]])
+        );
+        assert_eq!(
+            addresses[1].ip(),
+            IpAddr::from(<[u8; 16]>::try_from(&data[18..34]).unwrap())
+        );
+
+        let text = ips("abc", 1, false, true);
+        let address: SocketAddr = text.trim_end().parse().unwrap();
+        assert_eq!(
+            address.ip(),
+            IpAddr::from([data[0], data[1], data[2], data[3]])
+        );
+        assert_eq!(address.port(), u16::from_be_bytes([data[4], data[5]]));
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::{pick, ALPHANUMERIC};
+
+/// The letters of `ALPHANUMERIC`, which the keys start with
+const LETTERS: &[u8] = ALPHANUMERIC.split_at(52).0;
+
+/// Write `count` lines of `key=value` config pairs, where the key has `key_length` characters and the value
+/// `value_length` characters, both picked from the generated stream. The key starts with a letter, so that
+/// it is a valid identifier, and the rest of the key and the value are alphanumeric.
+pub fn write_kv_lines(
+    writer: InfiniteDataWriter,
+    count: u64,
+    key_length: usize,
+    value_length: usize,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    assert!(key_length > 0 && value_length > 0);
+
+    let mut bytes = writer.flatten();
+    let mut line = Vec::with_capacity(key_length + value_length + 2);
+
+    for _ in 0..count {
+        line.clear();
+        line.push(pick(&mut bytes, LETTERS));
+        line.extend((1..key_length).map(|_| pick(&mut bytes, ALPHANUMERIC)));
+        line.push(b'=');
+        line.extend((0..value_length).map(|_| pick(&mut bytes, ALPHANUMERIC)));
+        line.push(b'\n');
+
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kv_lines(seed: &str, count: u64, key_length: usize, value_length: usize) -> String {
+        let mut result = Vec::new();
+        write_kv_lines(
+            InfiniteDataWriter::new(seed),
+            count,
+            key_length,
+            value_length,
+            &mut result,
+        )
+        .unwrap();
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn lines_are_valid_pairs() {
+        let lines = kv_lines("abc", 1000, 6, 12);
+        assert_eq!(lines.lines().count(), 1000);
+        for line in lines.lines() {
+            // ^[A-Za-z][A-Za-z0-9]*=[A-Za-z0-9]+$
+            let (key, value) = line.split_once('=').unwrap();
+            assert_eq!((key.len(), value.len()), (6, 12), "{line}");
+            assert!(key.as_bytes()[0].is_ascii_alphabetic(), "{line}");
+            assert!(key.bytes().all(|b| b.is_ascii_alphanumeric()), "{line}");
+            assert!(value.bytes().all(|b| b.is_ascii_alphanumeric()), "{line}");
+        }
+        // Keys can still have digits after their first letter
+        assert!(lines
+            .lines()
+            .any(|line| line[1..6].bytes().any(|b| b.is_ascii_digit())));
+    }
+
+    #[test]
+    fn reproducible() {
+        let lines = kv_lines("abc", 100, 8, 16);
+        assert_eq!(lines, kv_lines("abc", 100, 8, 16));
+        assert_ne!(lines, kv_lines("abd", 100, 8, 16));
+        assert_eq!(kv_lines("abc", 3, 1, 1).len(), 12);
+    }
+}
@@ -0,0 +1,223 @@
+use std::{io::Write, str::FromStr};
+
+use rdgen_lib::InfiniteDataWriter;
+
+use crate::stream::GENERATION_BUFFER_SIZE;
+
+/// A field of a record layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// An unsigned little-endian integer with the given number of bytes
+    Integer(usize),
+    /// Raw bytes with the given length
+    Bytes(usize),
+}
+
+impl Field {
+    pub fn size(&self) -> usize {
+        match self {
+            Field::Integer(size) | Field::Bytes(size) => *size,
+        }
+    }
+}
+
+/// The layout of a binary record, parsed from a comma-separated list of fields, where every field is
+/// one of `u8`, `u16`, `u32`, `u64`, `u128` (little-endian integers) or `bytes[N]` (N raw bytes).
+/// For example: `u32,u16,bytes[10]`.
+///
+/// Every record is filled field by field from the generated stream, so the fields consume
+/// consecutive bytes of the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    fields: Vec<Field>,
+}
+
+impl Layout {
+    /// The size of a single record
+    pub fn record_size(&self) -> usize {
+        self.fields.iter().map(Field::size).sum()
+    }
+
+    /// Write a record from the generated stream, using `chunk` as the buffer. Records larger than the buffer,
+    /// with large `bytes[N]` fields, are written in chunks of the size of the buffer.
+    fn write_record(
+        &self,
+        bytes: &mut impl Iterator<Item = u8>,
+        chunk: &mut Vec<u8>,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        for field in &self.fields {
+            let mut remaining = field.size();
+            while remaining > 0 {
+                let length = remaining.min(GENERATION_BUFFER_SIZE - chunk.len());
+                chunk.extend(bytes.by_ref().take(length));
+                remaining -= length;
+                if chunk.len() == GENERATION_BUFFER_SIZE {
+                    out.write_all(chunk)?;
+                    chunk.clear();
+                }
+            }
+        }
+        out.write_all(chunk)?;
+        chunk.clear();
+        Ok(())
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s
+            .split(',')
+            .map(|field| {
+                let field = field.trim();
+                match field {
+                    "u8" => Ok(Field::Integer(1)),
+                    "u16" => Ok(Field::Integer(2)),
+                    "u32" => Ok(Field::Integer(4)),
+                    "u64" => Ok(Field::Integer(8)),
+                    "u128" => Ok(Field::Integer(16)),
+                    _ => field
+                        .strip_prefix("bytes[")
+                        .and_then(|f| f.strip_suffix(']'))
+                        .and_then(|size| size.trim().parse().ok())
+                        .map(Field::Bytes)
+                        .ok_or_else(|| format!("Invalid layout field: `{field}`")),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let record_size = fields
+            .iter()
+            .try_fold(0usize, |size, field| size.checked_add(field.size()))
+            .ok_or("Layout records are too large")?;
+        if record_size == 0 {
+            return Err("Layout records must not be empty".to_string());
+        }
+        Ok(Layout { fields })
+    }
+}
+
+/// Write `records` records with the given layout, back to back
+pub fn write_records(
+    writer: InfiniteDataWriter,
+    layout: &Layout,
+    records: u64,
+    separator: &[u8],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+    let mut chunk = Vec::with_capacity(layout.record_size().min(GENERATION_BUFFER_SIZE));
+
+    for i in 0..records {
+        if i > 0 {
+            out.write_all(separator)?;
+        }
+        layout.write_record(&mut bytes, &mut chunk, out)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(spec: &str, count: u64) -> Vec<u8> {
+        separated_records(spec, count, &[])
+    }
+
+    fn separated_records(spec: &str, count: u64, separator: &[u8]) -> Vec<u8> {
+        let layout: Layout = spec.parse().unwrap();
+        let mut result = Vec::new();
+        write_records(
+            InfiniteDataWriter::new("abc"),
+            &layout,
+            count,
+            separator,
+            &mut result,
+        )
+        .unwrap();
+        result
+    }
+
+    #[test]
+    fn parsing() {
+        let layout: Layout = "u32, u16,bytes[10] ,u8,u64,u128,bytes[ 3 ]"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            layout.fields,
+            [
+                Field::Integer(4),
+                Field::Integer(2),
+                Field::Bytes(10),
+                Field::Integer(1),
+                Field::Integer(8),
+                Field::Integer(16),
+                Field::Bytes(3),
+            ]
+        );
+        assert_eq!(layout.record_size(), 44);
+
+        for invalid in [
+            "",
+            "u32,",
+            "u24",
+            "bytes[]",
+            "bytes[x]",
+            "bytes[4",
+            "bytes[0]",
+            "bytes[18446744073709551615],u8",
+        ] {
+            assert!(invalid.parse::<Layout>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn stable_records() {
+        let data = records("u32,bytes[4]", 3);
+        assert_eq!(data.len(), 24);
+        let records: Vec<String> = data.chunks(8).map(hex::encode).collect();
+        assert_eq!(
+            records,
+            ["ba80a53f981c4d0d", "6a2797b69f12f6e9", "4c212f14685ac4b7"]
+        );
+    }
+
+    #[test]
+    fn separated_by_nul() {
+        let data = separated_records("bytes[8]", 2, &[0]);
+        assert_eq!(data.len(), 17);
+        assert_eq!(data[8], 0);
+        let expected: Vec<u8> = InfiniteDataWriter::new("abc").flatten().take(16).collect();
+        assert_eq!([&data[..8], &data[9..]].concat(), expected);
+
+        // Separators of any bytes, and none after the last record
+        let data = separated_records("u16", 3, b"\0\n");
+        assert_eq!(data.len(), 10);
+        assert_eq!(&data[2..4], b"\0\n");
+        assert_eq!(&data[6..8], b"\0\n");
+        assert_eq!(separated_records("u16", 1, b"--"), records("u16", 1));
+    }
+
+    #[test]
+    fn fields_consume_consecutive_bytes() {
+        let expected: Vec<u8> = InfiniteDataWriter::new("abc")
+            .flatten()
+            .take(31 * 5)
+            .collect();
+        assert_eq!(records("u8,u16,u128,bytes[12]", 5), expected);
+    }
+
+    #[test]
+    fn large_fields_in_chunks() {
+        let size = 2 * GENERATION_BUFFER_SIZE + 100;
+        let expected: Vec<u8> = InfiniteDataWriter::new("abc")
+            .flatten()
+            .take(2 * (size + 4))
+            .collect();
+        assert_eq!(records(&format!("u32,bytes[{size}]"), 2), expected);
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    io::Write,
+    net::{TcpListener, TcpStream},
+};
+
+use rdgen_lib::FiniteDataWriter;
+
+use crate::stream::{DataSource, GENERATION_BUFFER_SIZE};
+
+/// Serve the generated stream to every connection to the listener, from the start of the stream, with
+/// `length` bytes or without an end. Every client is served in its own thread, and the connection is closed
+/// once its stream ends. Clients may disconnect at any time, which only ends their connection.
+pub fn serve(listener: TcpListener, seed: [u8; 64], length: Option<u64>) -> std::io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            // A connection that failed before it was accepted does not stop the server
+            Err(_) => continue,
+        };
+        std::thread::spawn(move || {
+            let _ = serve_client(stream, FiniteDataWriter::from_seed_bytes(seed, length));
+        });
+    }
+    Ok(())
+}
+
+fn serve_client(mut stream: TcpStream, mut data_source: impl DataSource) -> std::io::Result<()> {
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+    loop {
+        let length = data_source.fill(&mut buffer);
+        if length == 0 {
+            return stream.flush();
+        }
+        match stream.write_all(&buffer[..length]) {
+            Ok(()) => {}
+            Err(e) if is_disconnect(&e) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn is_disconnect(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::BrokenPipe
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::ConnectionAborted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use rdgen_lib::InfiniteDataWriter;
+
+    use super::*;
+
+    #[test]
+    fn clients_read_the_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let seed = *InfiniteDataWriter::new("abc").seed();
+        std::thread::spawn(move || serve(listener, seed, Some(1000)));
+
+        // A client that disconnects early
+        let mut client = TcpStream::connect(address).unwrap();
+        let mut first_batch = [0; 64];
+        client.read_exact(&mut first_batch).unwrap();
+        assert_eq!(hex::encode(first_batch), "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923");
+        drop(client);
+
+        // The next client gets the whole stream from the start
+        let mut client = TcpStream::connect(address).unwrap();
+        let mut data = Vec::new();
+        client.read_to_end(&mut data).unwrap();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+        assert_eq!(data, expected);
+    }
+}
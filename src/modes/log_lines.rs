@@ -0,0 +1,115 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::{pick, ALPHANUMERIC};
+use crate::stream::GENERATION_BUFFER_SIZE;
+
+/// Write `count` pseudo-log lines, each with `line_length` payload characters.
+///
+/// The format of line `i` (starting from zero) is `HH:MM:SS <payload>\n`, where the timestamp
+/// is `i` seconds (hours are not wrapped), and the payload is made of alphanumeric characters
+/// picked from the generated stream. Long lines are written in chunks, so the memory does not grow with the
+/// line length.
+pub fn write_log_lines(
+    writer: InfiniteDataWriter,
+    count: u64,
+    line_length: usize,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+    let mut line = Vec::with_capacity(line_length.min(GENERATION_BUFFER_SIZE) + 16);
+
+    for index in 0..count {
+        line.clear();
+        write!(
+            line,
+            "{:02}:{:02}:{:02} ",
+            index / 3600,
+            (index / 60) % 60,
+            index % 60
+        )?;
+
+        let mut remaining = line_length;
+        loop {
+            let length = remaining.min(GENERATION_BUFFER_SIZE);
+            line.extend((0..length).map(|_| pick(&mut bytes, ALPHANUMERIC)));
+            remaining -= length;
+            if remaining == 0 {
+                break;
+            }
+            out.write_all(&line)?;
+            line.clear();
+        }
+        line.push(b'\n');
+
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_lines(seed: &str, count: u64, line_length: usize) -> Vec<u8> {
+        let mut result = Vec::new();
+        write_log_lines(
+            InfiniteDataWriter::new(seed),
+            count,
+            line_length,
+            &mut result,
+        )
+        .unwrap();
+        result
+    }
+
+    #[test]
+    fn reproducible() {
+        let lines = log_lines("abc", 100, 50);
+        assert_eq!(lines, log_lines("abc", 100, 50));
+        assert_ne!(lines, log_lines("abd", 100, 50));
+
+        // Fewer lines are a prefix of more lines
+        let fewer = log_lines("abc", 10, 50);
+        assert_eq!(fewer, lines[..fewer.len()]);
+    }
+
+    #[test]
+    fn printable() {
+        let lines = log_lines("abc", 100, 50);
+        assert!(lines
+            .iter()
+            .all(|b| b.is_ascii_graphic() || *b == b' ' || *b == b'\n'));
+    }
+
+    #[test]
+    fn format() {
+        let lines = log_lines("abc", 3700, 10);
+        let lines = String::from_utf8(lines).unwrap();
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 3700);
+        assert!(lines[0].starts_with("00:00:00 "));
+        assert!(lines[61].starts_with("00:01:01 "));
+        assert!(lines[3661].starts_with("01:01:01 "));
+        assert!(lines.iter().all(|l| l.len() == 9 + 10));
+        assert!(lines
+            .iter()
+            .all(|l| l[9..].bytes().all(|b| b.is_ascii_alphanumeric())));
+    }
+
+    #[test]
+    fn long_lines_in_chunks() {
+        let line_length = 2 * GENERATION_BUFFER_SIZE + 100;
+        let lines = String::from_utf8(log_lines("abc", 2, line_length)).unwrap();
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("00:00:01 "));
+        assert!(lines.iter().all(|l| l.len() == 9 + line_length));
+
+        // The payload is the same as with lines of one chunk
+        let short = String::from_utf8(log_lines("abc", 1, 100)).unwrap();
+        assert_eq!(lines[0][..109], short[..109]);
+    }
+}
@@ -0,0 +1,102 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use crate::stream::{Chunks, DataSource, GENERATION_BUFFER_SIZE};
+
+/// Write `count` numbered lines, each with `line_length` generated bytes, hex-encoded.
+///
+/// The format of line `i` (starting from one) is `NNNNNNNN <data>\n`, where the line number
+/// is zero-padded to 8 digits (and grows past them), and the data is the next bytes of the generated stream.
+/// Long lines are written in chunks, so the memory does not grow with the line length.
+pub fn write_numbered_lines(
+    writer: InfiniteDataWriter,
+    count: u64,
+    line_length: usize,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut data = Chunks::new(writer.map(|batch| batch.to_vec()));
+    let mut bytes = vec![0; line_length.min(GENERATION_BUFFER_SIZE)];
+    let mut line = Vec::with_capacity(2 * bytes.len() + 16);
+
+    for number in 1..=count {
+        line.clear();
+        write!(line, "{number:08} ")?;
+
+        let mut remaining = line_length;
+        loop {
+            let length = remaining.min(bytes.len());
+            data.fill(&mut bytes[..length]);
+            line.extend_from_slice(hex::encode(&bytes[..length]).as_bytes());
+            remaining -= length;
+            if remaining == 0 {
+                break;
+            }
+            out.write_all(&line)?;
+            line.clear();
+        }
+        line.push(b'\n');
+
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::FiniteDataWriter;
+
+    use super::*;
+
+    fn numbered_lines(seed: &str, count: u64, line_length: usize) -> String {
+        let mut result = Vec::new();
+        write_numbered_lines(
+            InfiniteDataWriter::new(seed),
+            count,
+            line_length,
+            &mut result,
+        )
+        .unwrap();
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn sequential_and_stable() {
+        let lines = numbered_lines("abc", 100, 10);
+        assert_eq!(lines, numbered_lines("abc", 100, 10));
+        assert_ne!(lines, numbered_lines("abd", 100, 10));
+
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 100);
+        assert_eq!(
+            lines[0],
+            format!(
+                "00000001 {}",
+                &"ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1"[..20]
+            )
+        );
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line[..9], format!("{:08} ", i + 1));
+        }
+
+        let data: String = lines.iter().map(|l| &l[9..]).collect();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(1000)).flatten().collect();
+        assert_eq!(data, hex::encode(expected));
+    }
+
+    #[test]
+    fn long_lines_in_chunks() {
+        let line_length = 2 * GENERATION_BUFFER_SIZE + 100;
+        let lines = numbered_lines("abc", 2, line_length);
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("00000002 "));
+
+        let data: String = lines.iter().map(|l| &l[9..]).collect();
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(2 * line_length as u64))
+            .flatten()
+            .collect();
+        assert_eq!(data, hex::encode(expected));
+    }
+}
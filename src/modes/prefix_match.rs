@@ -0,0 +1,94 @@
+use std::io::Read;
+
+use crate::stream::{DataSource, GENERATION_BUFFER_SIZE};
+
+/// Count how many leading bytes of `data` match the generated stream. The data is read in chunks
+/// and compared as it is read, until the first mismatch or the end of the data or of the generated stream.
+pub fn matching_prefix_length(
+    mut generated: impl DataSource,
+    mut data: impl Read,
+) -> std::io::Result<u64> {
+    let mut generated_buffer = vec![0; GENERATION_BUFFER_SIZE];
+    let (mut position_in_generated, mut generated_length) = (0, 0);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut matching = 0;
+
+    loop {
+        let read = match data.read(&mut buffer) {
+            Ok(0) => return Ok(matching),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for &byte in &buffer[..read] {
+            if position_in_generated == generated_length {
+                generated_length = generated.fill(&mut generated_buffer);
+                position_in_generated = 0;
+                if generated_length == 0 {
+                    return Ok(matching);
+                }
+            }
+            if byte != generated_buffer[position_in_generated] {
+                return Ok(matching);
+            }
+            position_in_generated += 1;
+            matching += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::{CounterDataWriter, FiniteDataWriter};
+
+    use super::*;
+
+    fn generated(length: u64) -> Vec<u8> {
+        FiniteDataWriter::new("abc", Some(length))
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn diverging_after_50_bytes() {
+        let mut data = generated(100);
+        data[50] ^= 1;
+        let matching =
+            matching_prefix_length(FiniteDataWriter::new("abc", None), &data[..]).unwrap();
+        assert_eq!(matching, 50);
+    }
+
+    #[test]
+    fn full_and_empty_matches() {
+        let data = generated(200_000);
+        let matching =
+            matching_prefix_length(FiniteDataWriter::new("abc", None), &data[..]).unwrap();
+        assert_eq!(matching, 200_000);
+
+        let matching = matching_prefix_length(FiniteDataWriter::new("abc", None), &[][..]).unwrap();
+        assert_eq!(matching, 0);
+
+        let matching =
+            matching_prefix_length(FiniteDataWriter::new("abd", None), &data[..]).unwrap();
+        assert_eq!(matching, 0);
+
+        // The generated stream ends before the data
+        let matching =
+            matching_prefix_length(FiniteDataWriter::new("abc", Some(100)), &data[..]).unwrap();
+        assert_eq!(matching, 100);
+    }
+
+    #[test]
+    fn other_generator() {
+        let data: Vec<u8> = CounterDataWriter::new("abc", Some(1000))
+            .flatten()
+            .collect();
+        let matching =
+            matching_prefix_length(CounterDataWriter::new("abc", None), &data[..]).unwrap();
+        assert_eq!(matching, 1000);
+        let matching =
+            matching_prefix_length(FiniteDataWriter::new("abc", None), &data[..]).unwrap();
+        assert_eq!(matching, 0);
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    io::Read,
+    process::ExitCode,
+    rc::Rc,
+};
+
+use anyhow::Context;
+
+use crate::{
+    exit_codes,
+    program_options::{Codec, RDGenOptions},
+    stream::{DataSource, SourceReader, GENERATION_BUFFER_SIZE},
+};
+
+/// The result of compressing the generated data and decompressing it again
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Roundtrip {
+    /// The number of generated bytes that were compared, which is all of them if the data matches
+    pub length: u64,
+    /// The number of bytes of the compressed data
+    pub compressed_length: u64,
+    /// The offset of the first byte of the decompressed data that differs from the generated data,
+    /// including the end of either of them
+    pub first_difference: Option<u64>,
+}
+
+impl Roundtrip {
+    /// The compression ratio: the length of the data over the length of the compressed data
+    pub fn ratio(&self) -> f64 {
+        self.length as f64 / self.compressed_length.max(1) as f64
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self.first_difference {
+            None => exit_codes::SUCCESS,
+            Some(_) => exit_codes::MISMATCH,
+        }
+    }
+}
+
+/// A reader of the generated data that keeps the bytes read, until they are compared with the decompressed data
+struct Tee<S> {
+    reader: SourceReader<S>,
+    pending: Rc<RefCell<VecDeque<u8>>>,
+}
+
+impl<S: DataSource> Read for Tee<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.reader.read(buf)?;
+        self.pending.borrow_mut().extend(&buf[..length]);
+        Ok(length)
+    }
+}
+
+/// A reader that counts the bytes read from the inner reader
+struct Counting<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        self.count.set(self.count.get() + length as u64);
+        Ok(length)
+    }
+}
+
+/// Run --roundtrip: compress and decompress the generated data with the codec of the options, and report
+/// the sizes, the compression ratio and the first difference, if any, to stderr
+pub fn run(args: &RDGenOptions, generated: impl DataSource) -> anyhow::Result<ExitCode> {
+    let codec = args
+        .roundtrip
+        .expect("The roundtrip mode is only run with --roundtrip");
+    let result =
+        roundtrip(generated, codec).context("Compressing and decompressing the data failed")?;
+    eprintln!(
+        "Roundtrip: {} bytes, {} compressed bytes, ratio {:.3}",
+        result.length,
+        result.compressed_length,
+        result.ratio()
+    );
+    if let Some(offset) = result.first_difference {
+        eprintln!("The decompressed data differs at offset {offset}");
+    }
+    Ok(ExitCode::from(result.exit_code()))
+}
+
+/// Compress the generated data with the given codec, decompress it, and compare the result with
+/// the generated data. Everything streams: the data is compressed as it is generated, and decompressed
+/// as it is compressed, so only the bytes in flight in the codec are held in memory.
+pub fn roundtrip<'a>(generated: impl DataSource + 'a, codec: Codec) -> std::io::Result<Roundtrip> {
+    use flate2::{bufread, Compression};
+
+    let pending = Rc::new(RefCell::new(VecDeque::new()));
+    let compressed_length = Rc::new(Cell::new(0));
+
+    let tee = std::io::BufReader::new(Tee {
+        reader: SourceReader::new(generated),
+        pending: pending.clone(),
+    });
+    let level = Compression::default();
+    let compressed: Box<dyn Read + 'a> = match codec {
+        Codec::Deflate => Box::new(bufread::DeflateEncoder::new(tee, level)),
+        Codec::Zlib => Box::new(bufread::ZlibEncoder::new(tee, level)),
+        Codec::Gzip => Box::new(bufread::GzEncoder::new(tee, level)),
+    };
+    let compressed = std::io::BufReader::new(Counting {
+        inner: compressed,
+        count: compressed_length.clone(),
+    });
+    let mut decompressed: Box<dyn Read + 'a> = match codec {
+        Codec::Deflate => Box::new(bufread::DeflateDecoder::new(compressed)),
+        Codec::Zlib => Box::new(bufread::ZlibDecoder::new(compressed)),
+        Codec::Gzip => Box::new(bufread::GzDecoder::new(compressed)),
+    };
+
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+    let mut offset = 0;
+    let first_difference = loop {
+        let length = match decompressed.read(&mut buffer) {
+            Ok(0) => break (!pending.borrow().is_empty()).then_some(offset),
+            Ok(length) => length,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        let mut pending = pending.borrow_mut();
+        let expected = length.min(pending.len());
+        let difference = pending
+            .drain(..expected)
+            .zip(&buffer[..length])
+            .position(|(a, &b)| a != b)
+            .or((expected < length).then_some(expected));
+        if let Some(i) = difference {
+            break Some(offset + i as u64);
+        }
+        offset += length as u64;
+    };
+
+    Ok(Roundtrip {
+        length: offset,
+        compressed_length: compressed_length.get(),
+        first_difference,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::FiniteDataWriter;
+
+    use super::*;
+    use crate::stream::Chunks;
+
+    #[test]
+    fn generated_data_roundtrips() {
+        for codec in [Codec::Deflate, Codec::Zlib, Codec::Gzip] {
+            for length in [0, 1, 1000, 200_000] {
+                let result = roundtrip(FiniteDataWriter::new("abc", Some(length)), codec).unwrap();
+                assert_eq!(result.first_difference, None, "{codec:?} {length}");
+                assert_eq!(result.length, length);
+                assert!(result.compressed_length > 0);
+                if length >= 1000 {
+                    // Generated data does not compress
+                    assert!(result.ratio() > 0.9 && result.ratio() <= 1., "{result:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn compressible_data_has_higher_ratio() {
+        let zeros = Chunks::new(std::iter::repeat(vec![0; 1000]).take(100));
+        let result = roundtrip(zeros, Codec::Gzip).unwrap();
+        assert_eq!(result.first_difference, None);
+        assert_eq!(result.length, 100_000);
+        assert!(result.ratio() > 10., "{result:?}");
+    }
+}
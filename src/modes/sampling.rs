@@ -0,0 +1,79 @@
+/// The characters used by the modes that emit alphanumeric text
+pub const ALPHANUMERIC: &[u8; 62] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Take the next byte from an infinite stream of generated bytes.
+pub fn next_byte(bytes: &mut impl Iterator<Item = u8>) -> u8 {
+    bytes.next().expect("The generated stream is infinite")
+}
+
+/// Pick one element of `alphabet` (at most 256 elements) uniformly, using the generated bytes.
+/// Each byte is mapped to `alphabet[byte % alphabet.len()]`, except for bytes in the last
+/// incomplete cycle of the alphabet, which are skipped to keep the selection unbiased.
+pub fn pick<T: Copy>(bytes: &mut impl Iterator<Item = u8>, alphabet: &[T]) -> T {
+    assert!(!alphabet.is_empty() && alphabet.len() <= 256);
+
+    let limit = 256 - 256 % alphabet.len();
+    loop {
+        let b = next_byte(bytes) as usize;
+        if b < limit {
+            return alphabet[b % alphabet.len()];
+        }
+    }
+}
+
+/// Pick a number in `0..n` uniformly, using the generated bytes. Every candidate is the next 8 bytes
+/// interpreted as a little-endian u64, and candidates above the largest multiple of `n` are skipped
+/// to keep the selection unbiased. The result is the candidate modulo `n`.
+pub fn uniform_below(bytes: &mut impl Iterator<Item = u8>, n: u64) -> u64 {
+    assert!(n > 0);
+
+    // 2^64 % n, computed without overflowing
+    let excess = (u64::MAX % n + 1) % n;
+    loop {
+        let mut candidate = [0; 8];
+        candidate.iter_mut().for_each(|b| *b = next_byte(bytes));
+        let candidate = u64::from_le_bytes(candidate);
+
+        if candidate <= u64::MAX - excess {
+            return candidate % n;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_skips_biased_bytes() {
+        // 256 % 62 == 8, so bytes 248..=255 must be skipped
+        let mut bytes = [255, 248, 247, 0, 61, 62].into_iter();
+        assert_eq!(pick(&mut bytes, ALPHANUMERIC), b'9');
+        assert_eq!(pick(&mut bytes, ALPHANUMERIC), b'A');
+        assert_eq!(pick(&mut bytes, ALPHANUMERIC), b'9');
+        assert_eq!(pick(&mut bytes, ALPHANUMERIC), b'A');
+    }
+
+    #[test]
+    fn uniform_below_skips_biased_candidates() {
+        // For n == 3, 2^64 % 3 == 1, so only u64::MAX must be skipped
+        let mut bytes = [u64::MAX, u64::MAX - 1, 7]
+            .into_iter()
+            .flat_map(u64::to_le_bytes);
+        assert_eq!(uniform_below(&mut bytes, 3), (u64::MAX - 1) % 3);
+        assert_eq!(uniform_below(&mut bytes, 3), 1);
+
+        let mut bytes = [u64::MAX].into_iter().flat_map(u64::to_le_bytes);
+        assert_eq!(uniform_below(&mut bytes, 1 << 32), u32::MAX as u64);
+    }
+
+    #[test]
+    fn pick_powers_of_two_use_every_byte() {
+        let alphabet: Vec<u16> = (0..256).collect();
+        let mut bytes = 0..=255;
+        for expected in 0..256 {
+            assert_eq!(pick(&mut bytes, &alphabet), expected);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::{Read, Write};
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::uniform_below;
+
+/// Read the newline-separated lines, without their newlines. The last line does not need a newline,
+/// and lines are otherwise kept as they are, including empty lines and carriage returns.
+pub fn read_lines(mut source: impl Read) -> std::io::Result<Vec<Vec<u8>>> {
+    let mut data = Vec::new();
+    source.read_to_end(&mut data)?;
+
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    let data = data.strip_suffix(b"\n").unwrap_or(&data);
+    Ok(data
+        .split(|b| *b == b'\n')
+        .map(|line| line.to_vec())
+        .collect())
+}
+
+/// Shuffle the items with the Fisher-Yates shuffle, where every swap is picked uniformly using
+/// the generated stream, so that every permutation is equally likely.
+pub fn shuffle<T>(writer: InfiniteDataWriter, items: &mut [T]) {
+    let mut bytes = writer.flatten();
+    for i in (1..items.len()).rev() {
+        let j = uniform_below(&mut bytes, i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Write the lines in the order of their shuffle, each followed by a newline
+pub fn write_shuffled_lines(
+    writer: InfiniteDataWriter,
+    mut lines: Vec<Vec<u8>>,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    shuffle(writer, &mut lines);
+    for line in lines {
+        out.write_all(&line)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shuffled(seed: &str, text: &str) -> String {
+        let lines = read_lines(text.as_bytes()).unwrap();
+        let mut result = Vec::new();
+        write_shuffled_lines(InfiniteDataWriter::new(seed), lines, &mut result).unwrap();
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(read_lines(&b"a\nb\n"[..]).unwrap(), [&b"a"[..], b"b"]);
+        assert_eq!(read_lines(&b"a\nb"[..]).unwrap(), [&b"a"[..], b"b"]);
+        assert_eq!(
+            read_lines(&b"a\r\n\nb\n\n"[..]).unwrap(),
+            [&b"a\r"[..], b"", b"b", b""]
+        );
+        assert!(read_lines(&b""[..]).unwrap().is_empty());
+        assert_eq!(read_lines(&b"\n"[..]).unwrap(), [&b""[..]]);
+    }
+
+    #[test]
+    fn known_permutation() {
+        let result = shuffled("abc", "one\ntwo\nthree\nfour\nfive");
+        assert_eq!(result, "one\ntwo\nfour\nfive\nthree\n");
+        assert_eq!(result, shuffled("abc", "one\ntwo\nthree\nfour\nfive\n"));
+        assert_ne!(result, shuffled("abd", "one\ntwo\nthree\nfour\nfive"));
+    }
+
+    #[test]
+    fn permutation_of_lines() {
+        let text: String = (0..1000).map(|i| format!("{i}\n")).collect();
+        let result = shuffled("abc", &text);
+        assert_ne!(result, text);
+
+        let mut lines: Vec<u32> = result.lines().map(|l| l.parse().unwrap()).collect();
+        lines.sort();
+        assert_eq!(lines, (0..1000).collect::<Vec<_>>());
+    }
+}
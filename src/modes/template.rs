@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+const PLACEHOLDER_START: &[u8] = b"{{rand:";
+const PLACEHOLDER_END: &[u8] = b"}}";
+
+/// Write the template, where every placeholder `{{rand:N}}` is replaced with N generated bytes, hex-encoded.
+/// The placeholders consume the generated stream one after the other, in the order they appear.
+pub fn write_template(
+    writer: InfiniteDataWriter,
+    template: &[u8],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+    let mut rest = template;
+
+    while let Some(start) = find(rest, PLACEHOLDER_START) {
+        out.write_all(&rest[..start])?;
+        rest = &rest[start + PLACEHOLDER_START.len()..];
+
+        let end = find(rest, PLACEHOLDER_END).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unterminated placeholder in template",
+            )
+        })?;
+        let length: usize = std::str::from_utf8(&rest[..end])
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Invalid length in template placeholder: {}",
+                        String::from_utf8_lossy(&rest[..end])
+                    ),
+                )
+            })?;
+        rest = &rest[end + PLACEHOLDER_END.len()..];
+
+        let data: Vec<u8> = bytes.by_ref().take(length).collect();
+        out.write_all(hex::encode(data).as_bytes())?;
+    }
+
+    out.write_all(rest)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(template: &str) -> std::io::Result<String> {
+        let mut out = Vec::new();
+        write_template(
+            InfiniteDataWriter::new("abc"),
+            template.as_bytes(),
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn two_placeholders() {
+        let filled = fill("key = \"{{rand:16}}\"\nid = {{rand:4}}\n").unwrap();
+        assert_eq!(
+            filled,
+            "key = \"ba80a53f981c4d0d6a2797b69f12f6e9\"\nid = 4c212f14\n"
+        );
+        assert_eq!(
+            fill("key = \"{{rand:16}}\"\nid = {{rand:4}}\n").unwrap(),
+            filled
+        );
+    }
+
+    #[test]
+    fn without_placeholders() {
+        assert_eq!(fill("").unwrap(), "");
+        assert_eq!(fill("{{other}} {rand:4}").unwrap(), "{{other}} {rand:4}");
+        assert_eq!(fill("{{rand:0}}").unwrap(), "");
+    }
+
+    #[test]
+    fn invalid_placeholders() {
+        assert!(fill("{{rand:4").is_err());
+        assert!(fill("{{rand:x}}").is_err());
+        assert!(fill("{{rand:-1}}").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::next_byte;
+
+/// Write valid UTF-8 text of about `length` bytes. Every character is made of the next 2 bytes of the generated
+/// stream, as a little-endian code point of the Basic Multilingual Plane, where surrogates (U+D800 to U+DFFF),
+/// which are not characters, are skipped. Characters take 1 to 3 bytes in UTF-8, so the text stops once the next
+/// character does not fit in `length`, and is 0 to 2 bytes shorter than `length`.
+pub fn write_utf8(
+    writer: InfiniteDataWriter,
+    length: u64,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+    let mut text = String::with_capacity(64 * 1024);
+    let mut written = 0;
+
+    loop {
+        let code_point = u16::from_le_bytes([next_byte(&mut bytes), next_byte(&mut bytes)]);
+        let c = match char::from_u32(code_point as u32) {
+            Some(c) => c,
+            None => continue,
+        };
+        if written + c.len_utf8() as u64 > length {
+            break;
+        }
+        text.push(c);
+        written += c.len_utf8() as u64;
+
+        if text.len() >= 63 * 1024 {
+            out.write_all(text.as_bytes())?;
+            text.clear();
+        }
+    }
+    out.write_all(text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(seed: &str, length: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_utf8(InfiniteDataWriter::new(seed), length, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn valid_and_reproducible() {
+        for length in [0, 1, 2, 3, 100, 200_000] {
+            let data = text("abc", length);
+            let s = std::str::from_utf8(&data).unwrap();
+            assert!(s.len() as u64 <= length && s.len() as u64 + 2 >= length);
+            assert_eq!(data, text("abc", length));
+        }
+        assert_ne!(text("abc", 100), text("abd", 100));
+    }
+
+    #[test]
+    fn characters_follow_the_stream() {
+        let data: Vec<u8> = InfiniteDataWriter::new("abc").flatten().take(2).collect();
+        let expected = char::from_u32(u16::from_le_bytes([data[0], data[1]]) as u32).unwrap();
+        let s = String::from_utf8(text("abc", 3)).unwrap();
+        assert_eq!(s.chars().next(), Some(expected));
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::Write;
+
+use rdgen_lib::InfiniteDataWriter;
+
+use super::sampling::pick;
+
+const CONSONANTS: &[u8; 21] = b"bcdfghjklmnpqrstvwxyz";
+const VOWELS: &[u8; 5] = b"aeiou";
+const WORD_LENGTHS: &[usize; 6] = &[3, 4, 5, 6, 7, 8];
+
+/// Write `count` pronounceable pseudo-words, separated by spaces, and followed by a newline.
+///
+/// Every word has a length from 3 to 8 letters, picked uniformly, and its letters alternate between
+/// consonants and vowels, starting with a consonant, where every letter is picked uniformly from
+/// the generated stream.
+pub fn write_words(
+    writer: InfiniteDataWriter,
+    count: u64,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut bytes = writer.flatten();
+    let mut word = Vec::with_capacity(WORD_LENGTHS[WORD_LENGTHS.len() - 1] + 1);
+
+    for index in 0..count {
+        word.clear();
+        if index > 0 {
+            word.push(b' ');
+        }
+
+        let length = pick(&mut bytes, WORD_LENGTHS);
+        word.extend((0..length).map(|i| match i % 2 {
+            0 => pick(&mut bytes, CONSONANTS),
+            _ => pick(&mut bytes, VOWELS),
+        }));
+
+        out.write_all(&word)?;
+    }
+
+    if count > 0 {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(seed: &str, count: u64) -> String {
+        let mut out = Vec::new();
+        write_words(InfiniteDataWriter::new(seed), count, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn stable_words() {
+        assert_eq!(
+            words("abc", 8),
+            "dab kirex suxe rohazako xijutu mitifefu fijetuf qome\n"
+        );
+        assert_ne!(words("abc", 5), words("abd", 5));
+        assert_eq!(words("abc", 0), "");
+    }
+
+    #[test]
+    fn letters_and_spaces() {
+        let text = words("abc", 1000);
+        let text = text.strip_suffix('\n').unwrap();
+        assert!(text.bytes().all(|b| b.is_ascii_lowercase() || b == b' '));
+
+        let words: Vec<&str> = text.split(' ').collect();
+        assert_eq!(words.len(), 1000);
+        for word in words {
+            assert!((3..=8).contains(&word.len()));
+            for (i, letter) in word.bytes().enumerate() {
+                assert_eq!(VOWELS.contains(&letter), i % 2 == 1, "{word}");
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::{Read, Write};
+
+use rdgen_lib::InfiniteDataWriter;
+
+/// Write `data` XORed byte for byte with the generated stream. The data is read in chunks,
+/// and the output has the same length as the data. Applying this twice with the same seed
+/// gives back the original data.
+pub fn write_xor(
+    mut writer: InfiniteDataWriter,
+    mut data: impl Read,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut batch = writer.pull();
+    let mut position_in_batch = 0;
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = match data.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for byte in &mut buffer[..read] {
+            if position_in_batch == batch.len() {
+                writer.pull_into(&mut batch);
+                position_in_batch = 0;
+            }
+            *byte ^= batch[position_in_batch];
+            position_in_batch += 1;
+        }
+
+        out.write_all(&buffer[..read])?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xor(seed: &str, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_xor(InfiniteDataWriter::new(seed), data, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn xor_with_stream() {
+        let stream: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(100))
+            .flatten()
+            .collect();
+        assert_eq!(xor("abc", &[0; 100]), stream);
+        assert!(xor("abc", &[]).is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let original: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let masked = xor("abc", &original);
+        assert_eq!(masked.len(), original.len());
+        assert_ne!(masked, original);
+        assert_eq!(xor("abc", &masked), original);
+        assert_ne!(xor("abd", &masked), original);
+    }
+}
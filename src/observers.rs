@@ -0,0 +1,22 @@
+//! Observers see all the generated data, without changing it, to report about it after the run.
+
+#[cfg(any(feature = "crc32", feature = "xxhash"))]
+mod checksum;
+mod digest_check;
+mod entropy;
+mod preview;
+mod rate_report;
+
+#[cfg(feature = "crc32")]
+pub use checksum::Crc32;
+#[cfg(feature = "xxhash")]
+pub use checksum::XxHash64;
+pub use digest_check::{DigestCheck, DigestCheckOutcome};
+pub use entropy::EntropyReport;
+pub use preview::Preview;
+pub use rate_report::RateReport;
+
+/// Something that observes every chunk of generated data as it is written.
+pub trait Observer {
+    fn observe(&mut self, chunk: &[u8]);
+}
@@ -0,0 +1,105 @@
+//! Fast checksums of the generated data, for integrity checks. They are not cryptographic, so unlike
+//! Blake2b, they do not protect against deliberate changes of the data.
+
+use super::Observer;
+
+/// The CRC-32 (IEEE) checksum of the generated data, reported with --crc32
+#[cfg(feature = "crc32")]
+pub struct Crc32 {
+    hasher: crc32fast::Hasher,
+}
+
+#[cfg(feature = "crc32")]
+impl Crc32 {
+    pub fn new() -> Self {
+        Self {
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    pub fn finish(self) -> u32 {
+        self.hasher.finalize()
+    }
+}
+
+#[cfg(feature = "crc32")]
+impl Observer for Crc32 {
+    fn observe(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+}
+
+/// The XXH64 hash, with seed 0, of the generated data, reported with --xxhash
+#[cfg(feature = "xxhash")]
+pub struct XxHash64 {
+    hasher: xxhash_rust::xxh64::Xxh64,
+}
+
+#[cfg(feature = "xxhash")]
+impl XxHash64 {
+    pub fn new() -> Self {
+        Self {
+            hasher: xxhash_rust::xxh64::Xxh64::new(0),
+        }
+    }
+
+    pub fn finish(self) -> u64 {
+        self.hasher.digest()
+    }
+}
+
+#[cfg(feature = "xxhash")]
+impl Observer for XxHash64 {
+    fn observe(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "crc32")]
+    #[test]
+    fn crc32_vectors() {
+        let crc = |chunks: &[&[u8]]| {
+            let mut crc = Crc32::new();
+            chunks.iter().for_each(|c| crc.observe(c));
+            crc.finish()
+        };
+        assert_eq!(crc(&[]), 0);
+        assert_eq!(crc(&[b"123456789"]), 0xcbf4_3926);
+        assert_eq!(crc(&[b"1234", b"56789"]), 0xcbf4_3926);
+
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(100))
+            .flatten()
+            .collect();
+        assert_eq!(crc(&[&data]), 0x0817_71b1);
+    }
+
+    #[cfg(feature = "xxhash")]
+    #[test]
+    fn xxhash_vectors() {
+        let xxhash = |chunks: &[&[u8]]| {
+            let mut hash = XxHash64::new();
+            chunks.iter().for_each(|c| hash.observe(c));
+            hash.finish()
+        };
+        assert_eq!(xxhash(&[]), 0xef46_db37_51d8_e999);
+        assert_eq!(xxhash(&[b"abc"]), 0x44bc_2cf5_ad77_0999);
+        let text = b"Nobody inspects the spammish repetition";
+        assert_eq!(xxhash(&[text]), 0xfbce_a83c_8a37_8bf1);
+
+        // The hash does not depend on how the data is split into chunks
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(1000))
+            .flatten()
+            .collect();
+        let whole = xxhash(&[&data]);
+        for split in [1, 5, 31, 32, 33, 100, 999] {
+            let (a, b) = data.split_at(split);
+            assert_eq!(xxhash(&[a, b]), whole, "{split}");
+        }
+        let bytes: Vec<&[u8]> = data.chunks(1).collect();
+        assert_eq!(xxhash(&bytes), whole);
+    }
+}
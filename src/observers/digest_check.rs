@@ -0,0 +1,105 @@
+use blake2::{Blake2b512, Digest};
+
+use crate::exit_codes;
+
+use super::Observer;
+
+/// Computes the Blake2b-512 digest of the generated data, to compare it with an expected digest.
+pub struct DigestCheck {
+    hasher: Blake2b512,
+    expected: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DigestCheckOutcome {
+    Match,
+    Mismatch { actual: Vec<u8> },
+}
+
+impl DigestCheckOutcome {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            DigestCheckOutcome::Match => exit_codes::SUCCESS,
+            DigestCheckOutcome::Mismatch { .. } => exit_codes::MISMATCH,
+        }
+    }
+}
+
+impl DigestCheck {
+    pub fn new(expected: Vec<u8>) -> anyhow::Result<Self> {
+        if expected.len() != <Blake2b512 as Digest>::output_size() {
+            return Err(anyhow::anyhow!(
+                "Expected digest must be a {}-byte Blake2b-512 digest, but {} bytes were provided",
+                <Blake2b512 as Digest>::output_size(),
+                expected.len()
+            ));
+        }
+
+        Ok(Self {
+            hasher: Blake2b512::new(),
+            expected,
+        })
+    }
+
+    pub fn expected(&self) -> &[u8] {
+        &self.expected
+    }
+
+    pub fn finish(self) -> DigestCheckOutcome {
+        let actual = self.hasher.finalize().to_vec();
+        if actual == self.expected {
+            DigestCheckOutcome::Match
+        } else {
+            DigestCheckOutcome::Mismatch { actual }
+        }
+    }
+}
+
+impl Observer for DigestCheck {
+    fn observe(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdgen_lib::FiniteDataWriter;
+
+    use super::*;
+
+    fn check(expected_hex: &str) -> DigestCheckOutcome {
+        let mut check = DigestCheck::new(hex::decode(expected_hex).unwrap()).unwrap();
+        for batch in FiniteDataWriter::new("abc", Some(100)) {
+            check.observe(&batch);
+        }
+        check.finish()
+    }
+
+    // Blake2b-512 of the first 100 bytes of the stream of "abc", as computed by `b2sum`
+    const DIGEST: &str = "49734b4fc4c157c6cc2e532305f051c85dc70044f573fe5381127434c2327769abee10498d1818ac67da017066d07703a3010276fa7f39299c1a71a327d7fba4";
+
+    #[test]
+    fn correct_digest_passes() {
+        let outcome = check(DIGEST);
+        assert_eq!(outcome, DigestCheckOutcome::Match);
+        assert_eq!(outcome.exit_code(), exit_codes::SUCCESS);
+    }
+
+    #[test]
+    fn wrong_digest_fails() {
+        let wrong = DIGEST.replace('a', "b");
+        let outcome = check(&wrong);
+        assert_eq!(
+            outcome,
+            DigestCheckOutcome::Mismatch {
+                actual: hex::decode(DIGEST).unwrap()
+            }
+        );
+        assert_eq!(outcome.exit_code(), exit_codes::MISMATCH);
+    }
+
+    #[test]
+    fn wrong_digest_size() {
+        assert!(DigestCheck::new(vec![0; 32]).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::Write;
+
+use super::Observer;
+
+/// Counts how often every byte value occurs in the generated data, to estimate its Shannon entropy,
+/// reported with --entropy-report.
+pub struct EntropyReport {
+    counts: [u64; 256],
+    total: u64,
+}
+
+impl EntropyReport {
+    pub fn new() -> Self {
+        Self {
+            counts: [0; 256],
+            total: 0,
+        }
+    }
+
+    /// The Shannon entropy of the byte frequencies, in bits per byte, from 0 to 8.
+    /// Uniformly distributed data is close to 8.
+    pub fn entropy(&self) -> f64 {
+        if self.total == 0 {
+            return 0.;
+        }
+        self.counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / self.total as f64;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Write the summary as `key: value` lines, with the least and the most frequent byte values
+    pub fn write_report(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let by_count = |&b: &u8| self.counts[b as usize];
+        let least = (0..=255u8)
+            .min_by_key(by_count)
+            .expect("There are byte values");
+        let most = (0..=255u8)
+            .max_by_key(by_count)
+            .expect("There are byte values");
+
+        writeln!(out, "bytes: {}", self.total)?;
+        writeln!(out, "entropy (bits/byte): {:.6}", self.entropy())?;
+        writeln!(
+            out,
+            "least frequent byte: 0x{least:02x} ({} times)",
+            self.counts[least as usize]
+        )?;
+        writeln!(
+            out,
+            "most frequent byte: 0x{most:02x} ({} times)",
+            self.counts[most as usize]
+        )?;
+        Ok(())
+    }
+}
+
+impl Observer for EntropyReport {
+    fn observe(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            self.counts[byte as usize] += 1;
+        }
+        self.total += chunk.len() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_data_is_close_to_8_bits() {
+        let mut report = EntropyReport::new();
+        for chunk in rdgen_lib::FiniteDataWriter::new("abc", Some(1024 * 1024)) {
+            report.observe(&chunk);
+        }
+        assert!(report.entropy() > 7.999, "{}", report.entropy());
+        assert!(report.entropy() <= 8.);
+
+        let mut out = Vec::new();
+        report.write_report(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("bytes: 1048576\nentropy (bits/byte): 7.99"));
+    }
+
+    #[test]
+    fn skewed_data() {
+        let mut report = EntropyReport::new();
+        assert_eq!(report.entropy(), 0.);
+        report.observe(&[0; 100]);
+        assert_eq!(report.entropy(), 0.);
+        report.observe(&[1; 100]);
+        assert!((report.entropy() - 1.).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use super::Observer;
+use crate::output::{Encoding, HexDump};
+
+/// Writes a hexdump of the first bytes of the generated data, reported with --preview, while the data
+/// itself is written unchanged. The hexdump is finished as soon as it has all its bytes, so it can be
+/// seen while the rest of the data is generated.
+pub struct Preview<W: Write> {
+    dump: Option<Encoding<HexDump, W>>,
+    remaining: u64,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> Preview<W> {
+    pub fn new(out: W, length: u64) -> Self {
+        let mut preview = Self {
+            dump: Some(Encoding::new(HexDump::new(), out)),
+            remaining: length,
+            error: None,
+        };
+        if length == 0 {
+            preview.finish_dump();
+        }
+        preview
+    }
+
+    fn finish_dump(&mut self) {
+        if let Some(dump) = self.dump.take() {
+            if let Err(e) = dump.finish().and_then(|mut out| out.flush()) {
+                self.error.get_or_insert(e);
+            }
+        }
+    }
+
+    /// Finish the hexdump, if the data was shorter than the preview, and return the first error
+    /// of writing the hexdump, if any
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.finish_dump();
+        self.error.map_or(Ok(()), Err)
+    }
+}
+
+impl<W: Write> Observer for Preview<W> {
+    fn observe(&mut self, chunk: &[u8]) {
+        let Some(dump) = &mut self.dump else {
+            return;
+        };
+        let taken = (chunk.len() as u64).min(self.remaining) as usize;
+        if let Err(e) = dump.write_all(&chunk[..taken]) {
+            self.error.get_or_insert(e);
+        }
+        self.remaining -= taken as u64;
+        if self.remaining == 0 {
+            self.finish_dump();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hexdump(data: &[u8]) -> Vec<u8> {
+        crate::output::encode_all(HexDump::new(), &[data])
+    }
+
+    #[test]
+    fn previews_leading_bytes() {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(1000))
+            .flatten()
+            .collect();
+
+        for length in [0, 1, 20, 64, 1000, 5000] {
+            let mut out = Vec::new();
+            let mut preview = Preview::new(&mut out, length);
+            for chunk in data.chunks(100) {
+                preview.observe(chunk);
+            }
+            preview.finish().unwrap();
+            assert_eq!(out, hexdump(&data[..(length as usize).min(data.len())]));
+        }
+    }
+}
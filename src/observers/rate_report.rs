@@ -0,0 +1,167 @@
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use super::Observer;
+
+/// The time over which every sample of the throughput is measured
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The number of buckets of the histogram
+const BUCKETS: usize = 8;
+
+/// The width of the bar of the largest bucket of the histogram
+const BAR_WIDTH: u64 = 40;
+
+/// Samples the throughput of the output over the run, reported with --rate-report, to check how the output
+/// was paced. Every sample is the throughput of the data observed over about 100 milliseconds.
+pub struct RateReport {
+    sample_start: Instant,
+    sample_bytes: u64,
+    /// The throughput of every complete sample, in MiB/s
+    rates: Vec<f64>,
+}
+
+impl RateReport {
+    pub fn new() -> Self {
+        Self::start_at(Instant::now())
+    }
+
+    fn start_at(now: Instant) -> Self {
+        Self {
+            sample_start: now,
+            sample_bytes: 0,
+            rates: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, bytes: u64, now: Instant) {
+        self.sample_bytes += bytes;
+        if now.duration_since(self.sample_start) >= SAMPLE_INTERVAL {
+            self.end_sample(now);
+        }
+    }
+
+    fn end_sample(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.sample_start).as_secs_f64();
+        if elapsed > 0. {
+            self.rates
+                .push(self.sample_bytes as f64 / elapsed / (1024. * 1024.));
+        }
+        self.sample_start = now;
+        self.sample_bytes = 0;
+    }
+
+    /// Finish the last sample, and write the percentiles of the samples as `key: value` lines,
+    /// followed by a histogram of the samples, with a line for every bucket of throughputs.
+    pub fn write_report(mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.sample_bytes > 0 {
+            self.end_sample(Instant::now());
+        }
+        self.write_samples(out)
+    }
+
+    fn write_samples(mut self, out: &mut impl Write) -> std::io::Result<()> {
+        self.rates.sort_by(f64::total_cmp);
+        let rates = self.rates;
+
+        writeln!(out, "rate samples: {}", rates.len())?;
+        let (Some(&min), Some(&max)) = (rates.first(), rates.last()) else {
+            return Ok(());
+        };
+        for (name, percentile) in [("p50", 50), ("p90", 90), ("p99", 99)] {
+            // The nearest rank
+            let rank = (rates.len() * percentile).div_ceil(100).max(1);
+            writeln!(out, "rate {name} (MiB/s): {:.3}", rates[rank - 1])?;
+        }
+        writeln!(out, "rate min (MiB/s): {min:.3}")?;
+        writeln!(out, "rate max (MiB/s): {max:.3}")?;
+
+        let width = (max - min) / BUCKETS as f64;
+        let mut counts = [0u64; BUCKETS];
+        for rate in &rates {
+            let bucket = if width > 0. {
+                (((rate - min) / width) as usize).min(BUCKETS - 1)
+            } else {
+                0
+            };
+            counts[bucket] += 1;
+        }
+        let largest = counts.iter().copied().max().unwrap_or(0);
+        let buckets = if width > 0. { BUCKETS } else { 1 };
+        for (i, &count) in counts.iter().enumerate().take(buckets) {
+            let low = min + width * i as f64;
+            let high = if i == buckets - 1 { max } else { low + width };
+            let bar = "#".repeat((count * BAR_WIDTH).div_ceil(largest) as usize);
+            let line = format!("{low:>12.3} - {high:>12.3}: {count:>6} {bar}");
+            writeln!(out, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl Observer for RateReport {
+    fn observe(&mut self, chunk: &[u8]) {
+        self.record(chunk.len() as u64, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(report: RateReport) -> String {
+        let mut result = Vec::new();
+        report.write_samples(&mut result).unwrap();
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn percentiles_and_histogram() {
+        let start = Instant::now();
+        let mut rates = RateReport::start_at(start);
+        // 10 samples of 100 ms, with 1 to 10 MiB/s
+        for i in 1..=10u64 {
+            rates.record(i * 1024 * 1024 / 10, start + SAMPLE_INTERVAL * i as u32);
+        }
+        let report = report(rates);
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(
+            lines[..6],
+            [
+                "rate samples: 10",
+                "rate p50 (MiB/s): 5.000",
+                "rate p90 (MiB/s): 9.000",
+                "rate p99 (MiB/s): 10.000",
+                "rate min (MiB/s): 1.000",
+                "rate max (MiB/s): 10.000",
+            ]
+        );
+        let counts: Vec<u64> = lines[6..]
+            .iter()
+            .map(|l| l.split_whitespace().nth(3).unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(counts.len(), BUCKETS);
+        assert_eq!(counts.iter().sum::<u64>(), 10);
+    }
+
+    #[test]
+    fn short_samples_are_combined() {
+        let start = Instant::now();
+        let mut rates = RateReport::start_at(start);
+        for i in 1..=10u32 {
+            rates.record(1024 * 1024, start + SAMPLE_INTERVAL * i / 10);
+        }
+        let report = report(rates);
+        assert!(report.starts_with("rate samples: 1\nrate p50 (MiB/s): 100.000\n"));
+        // A single bucket when all the samples are equal
+        assert_eq!(report.lines().count(), 7);
+    }
+
+    #[test]
+    fn empty_run() {
+        assert_eq!(report(RateReport::new()), "rate samples: 0\n");
+    }
+}
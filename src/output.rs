@@ -0,0 +1,488 @@
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+    process::{Child, Command, Stdio},
+};
+
+use anyhow::Context;
+
+use crate::{
+    observers::Observer,
+    stream::{DataSource, GENERATION_BUFFER_SIZE},
+};
+
+mod align;
+#[cfg(feature = "base32")]
+mod base32;
+#[cfg(feature = "base58")]
+mod base58;
+mod base64;
+mod burst;
+mod encoder;
+mod flush_every;
+mod hexdump;
+#[cfg(all(unix, feature = "signals"))]
+mod interrupt;
+mod line_endings;
+mod max_chunk;
+mod pacing;
+#[cfg(all(unix, feature = "signals"))]
+mod pause;
+mod reverse;
+#[cfg(test)]
+mod time_recorder;
+
+pub use align::Align;
+#[cfg(feature = "base32")]
+pub use base32::Base32;
+#[cfg(feature = "base58")]
+pub use base58::Base58;
+pub use base64::Base64;
+pub use burst::{Burst, Bursty};
+#[cfg(test)]
+pub use encoder::encode_all;
+pub use encoder::{Encoder, Encoding, Hex};
+pub use flush_every::FlushEvery;
+pub use hexdump::HexDump;
+#[cfg(all(unix, feature = "signals"))]
+pub use interrupt::{install_interrupt_signals, interruption, Interruptible};
+pub use line_endings::LineEndings;
+pub use max_chunk::MaxChunk;
+pub use pacing::{Paced, TimingProfile};
+#[cfg(all(unix, feature = "signals"))]
+pub use pause::{install_pause_signals, Pausable};
+pub use reverse::{write_converging, write_reverse};
+
+/// The capacity of the buffer used for writing the output
+pub const OUTPUT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Where the generated data is written to
+#[derive(Debug, Clone, Copy)]
+pub enum Destination<'a> {
+    Stdout,
+    File(&'a Path, FileMode),
+    /// The stdin of a child process, spawned from the given command line
+    Command(&'a str),
+}
+
+/// How an existing output file is treated when it is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileMode {
+    /// Overwrite the file
+    Truncate,
+    /// Write after the current content of the file
+    Append,
+    /// Overwrite the file, after reserving the given number of bytes for it on the filesystem
+    Preallocate(u64),
+    /// Overwrite the start of an existing file or device in place, without truncating it.
+    /// A file that does not exist is created.
+    InPlace,
+}
+
+/// The layers of writers between the generated data and the destination, from the outermost
+type LayeredWriter = FlushEvery<BufWriter<MaxChunk<Align<Box<dyn Write>>>>>;
+
+/// The destination of the generated data, opened for writing. The output is buffered, and the buffer
+/// is flushed every `flush_every` bytes, with at most `max_chunk` bytes per write to the destination,
+/// and every write but the last is a multiple of `align` bytes. After writing, the output must be finished
+/// with `finish`.
+pub struct Output {
+    writer: LayeredWriter,
+    child: Option<Child>,
+}
+
+impl Output {
+    pub fn open(
+        destination: Destination,
+        flush_every: usize,
+        max_chunk: usize,
+        align: usize,
+    ) -> anyhow::Result<Self> {
+        let (writer, child): (Box<dyn Write>, _) = match destination {
+            Destination::Stdout => (Box::new(std::io::stdout().lock()), None),
+            Destination::File(p, FileMode::Truncate) => {
+                let f = std::fs::File::create(p)
+                    .context(format!("Creating output file failed: {}", p.display()))?;
+                (Box::new(f), None)
+            }
+            Destination::File(p, FileMode::Preallocate(size)) => {
+                let f = std::fs::File::create(p)
+                    .context(format!("Creating output file failed: {}", p.display()))?;
+                fs2::FileExt::allocate(&f, size).context(format!(
+                    "Preallocating {size} bytes for output file failed: {}",
+                    p.display()
+                ))?;
+                (Box::new(f), None)
+            }
+            Destination::File(p, FileMode::Append) => {
+                let f = std::fs::OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(p)
+                    .context(format!(
+                        "Opening output file for appending failed: {}",
+                        p.display()
+                    ))?;
+                (Box::new(f), None)
+            }
+            Destination::File(p, FileMode::InPlace) => {
+                let f = std::fs::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(p)
+                    .context(format!("Opening output file failed: {}", p.display()))?;
+                (Box::new(f), None)
+            }
+            Destination::Command(command) => {
+                let mut child = spawn_command(command, Stdio::inherit())?;
+                let stdin = child.stdin.take().expect("Child stdin is piped");
+                (Box::new(stdin), Some(child))
+            }
+        };
+
+        let buffered = BufWriter::with_capacity(
+            OUTPUT_BUFFER_SIZE,
+            MaxChunk::new(Align::new(writer, align), max_chunk),
+        );
+        Ok(Self {
+            writer: FlushEvery::new(buffered, flush_every),
+            child,
+        })
+    }
+
+    /// Close the output, given the result of writing to it, and return the exit code the program should use.
+    /// If the output is a child process, its stdin is closed and its exit code is returned, where a child that
+    /// stopped reading early (a broken pipe) is not considered an error of writing.
+    pub fn finish(self, write_result: anyhow::Result<()>) -> anyhow::Result<u8> {
+        let Self { writer, child } = self;
+        // The writer is dropped either way, which closes the stdin of the child, if any
+        let write_result = match write_result {
+            Ok(()) => finish_writer(writer),
+            Err(e) => Err(e),
+        };
+
+        let mut child = match child {
+            Some(c) => c,
+            None => return write_result.map(|()| crate::exit_codes::SUCCESS),
+        };
+
+        let status = child
+            .wait()
+            .context("Waiting for the child process failed")?;
+        if let Err(e) = write_result {
+            if !is_broken_pipe(&e) {
+                return Err(e);
+            }
+        }
+
+        // A child terminated by a signal has no exit code
+        Ok(status
+            .code()
+            .map_or(1, |code| u8::try_from(code).unwrap_or(1)))
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Write out everything still held by the layers of the writer
+fn finish_writer(writer: LayeredWriter) -> anyhow::Result<()> {
+    let max_chunk = writer
+        .into_inner()
+        .into_inner()
+        .map_err(|e| e.into_error())
+        .context("Flushing the output failed")?;
+    max_chunk
+        .into_inner()
+        .finish()
+        .context("Writing the end of the output failed")?;
+    Ok(())
+}
+
+/// Spawn the command line, split by whitespace (without any shell quoting), with a piped stdin
+fn spawn_command(command: &str, stdout: Stdio) -> anyhow::Result<Child> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The command to pipe to is empty"))?;
+
+    Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .stdout(stdout)
+        .spawn()
+        .context(format!("Spawning command failed: {command}"))
+}
+
+fn is_broken_pipe(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|e| e.downcast_ref::<std::io::Error>())
+        .any(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+}
+
+/// Run the given write operation on the output, then explicitly flush the output, even if the
+/// operation failed, so that whatever was written before the failure reaches the destination.
+/// The error of the operation takes precedence over a flush error.
+pub fn write_and_flush<W: Write + ?Sized>(
+    out: &mut W,
+    write: impl FnOnce(&mut W) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let result = write(out);
+    let flush_result = out.flush().context("Flushing the output failed");
+    result.and(flush_result)
+}
+
+/// Write all the generated data to the output, between the given fixed prefix and suffix bytes,
+/// showing the generated data to the given observers. The data is generated into a single buffer
+/// of `GENERATION_BUFFER_SIZE` bytes, which is reused until the data ends.
+pub fn write_stream(
+    prefix: &[u8],
+    mut data_source: impl DataSource,
+    suffix: &[u8],
+    out: &mut (impl Write + ?Sized),
+    observers: &mut [&mut dyn Observer],
+) -> anyhow::Result<()> {
+    out.write_all(prefix)
+        .context("Writing prefix to output failed")?;
+
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+    loop {
+        let length = data_source.fill(&mut buffer);
+        if length == 0 {
+            break;
+        }
+        let data = &buffer[..length];
+        out.write_all(data)
+            .context("Writing result to output failed")?;
+        observers.iter_mut().for_each(|o| o.observe(data));
+    }
+
+    out.write_all(suffix)
+        .context("Writing suffix to output failed")?;
+    Ok(())
+}
+
+/// Same as `write_stream`, but encodes everything written with the given encoder, and finalizes the encoding
+pub fn write_encoded_stream(
+    prefix: &[u8],
+    data_source: impl DataSource,
+    suffix: &[u8],
+    encoder: impl Encoder,
+    out: &mut (impl Write + ?Sized),
+    observers: &mut [&mut dyn Observer],
+) -> anyhow::Result<()> {
+    let mut encoding = Encoding::new(encoder, out);
+    write_stream(prefix, data_source, suffix, &mut encoding, observers)?;
+    encoding
+        .finish()
+        .context("Writing encoding to output failed")?;
+    Ok(())
+}
+
+/// Make sure that the filesystem of the given output path has enough free space to hold `length` bytes.
+/// If the output file is truncated when opened, the space it currently occupies is counted as available.
+pub fn check_available_space(p: &Path, length: u64, mode: FileMode) -> anyhow::Result<()> {
+    let existing_size = match std::fs::metadata(p) {
+        Ok(m) if m.is_file() && mode != FileMode::Append => m.len(),
+        _ => 0,
+    };
+
+    // The file may not exist yet, so we query the directory that will contain it
+    let dir = match p.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+
+    let available = fs2::available_space(dir)
+        .context(format!("Querying free space failed for: {}", dir.display()))?
+        .saturating_add(existing_size);
+
+    if length > available {
+        return Err(anyhow::anyhow!(
+            "Not enough free space to write {} bytes to {}; only {} bytes are available",
+            length,
+            p.display(),
+            available
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::{Blocks, RunStats};
+
+    use super::*;
+
+    #[test]
+    fn stats_do_not_change_output() {
+        let mut stats = RunStats::start(Blocks::Chain { batch_size: 64 }, 0, true);
+        let mut out = Vec::new();
+        write_stream(
+            &[],
+            rdgen_lib::FiniteDataWriter::new("abc", Some(100)),
+            &[],
+            &mut out,
+            &mut [&mut stats],
+        )
+        .unwrap();
+        let mut report = Vec::new();
+        stats.write_report(&mut report).unwrap();
+
+        assert!(report.starts_with(b"bytes: 100\n"));
+        assert_eq!(out.len(), 100);
+        assert_eq!(
+            out,
+            rdgen_lib::FiniteDataWriter::new("abc", Some(100))
+                .flatten()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn prefix_and_suffix() {
+        let prefix = [0xde, 0xad, 0xbe, 0xef];
+        let suffix = [0xca, 0xfe];
+        let expected: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(100))
+            .flatten()
+            .collect();
+
+        let mut out = Vec::new();
+        write_stream(
+            &prefix,
+            rdgen_lib::FiniteDataWriter::new("abc", Some(100)),
+            &suffix,
+            &mut out,
+            &mut [],
+        )
+        .unwrap();
+
+        assert_eq!(out.len(), 106);
+        assert_eq!(out[..4], prefix);
+        assert_eq!(out[4..104], expected);
+        assert_eq!(out[104..], suffix);
+
+        // The markers appear exactly once
+        let count = |marker: &[u8]| out.windows(marker.len()).filter(|w| *w == marker).count();
+        assert_eq!(count(&prefix), 1);
+        assert_eq!(count(&suffix), 1);
+    }
+
+    #[test]
+    fn flush_after_successful_write() {
+        let mut sink = Vec::new();
+        {
+            let mut out = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, &mut sink);
+            write_and_flush(&mut out, |out| Ok(out.write_all(&[1; 100])?)).unwrap();
+            assert_eq!(out.get_ref().len(), 100);
+        }
+        assert_eq!(sink, [1; 100]);
+    }
+
+    #[test]
+    fn flush_after_failed_write() {
+        let mut sink = Vec::new();
+        let mut out = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, &mut sink);
+        let result = write_and_flush(&mut out, |out| {
+            out.write_all(&[1; 100])?;
+            Err(anyhow::anyhow!("Generation failed"))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "Generation failed");
+
+        // The buffered prefix reached the sink while the buffered writer is still alive
+        assert_eq!(out.get_ref().as_slice(), [1; 100]);
+    }
+
+    #[cfg(unix)]
+    fn run_through_command(command: &str, length: u64) -> (u8, Vec<u8>) {
+        use std::io::Read;
+
+        let mut child = spawn_command(command, Stdio::piped()).unwrap();
+        let mut child_stdout = child.stdout.take().unwrap();
+        let stdin: Box<dyn Write> = Box::new(child.stdin.take().unwrap());
+        let mut output = Output {
+            writer: FlushEvery::new(
+                BufWriter::new(MaxChunk::new(Align::new(stdin, 1), OUTPUT_BUFFER_SIZE)),
+                OUTPUT_BUFFER_SIZE,
+            ),
+            child: Some(child),
+        };
+
+        // The child output is read concurrently, so that the child never blocks on writing it
+        let reader = std::thread::spawn(move || {
+            let mut captured = Vec::new();
+            child_stdout.read_to_end(&mut captured).unwrap();
+            captured
+        });
+
+        let result = write_and_flush(&mut output, |out| {
+            write_stream(
+                &[],
+                rdgen_lib::FiniteDataWriter::new("abc", Some(length)),
+                &[],
+                out,
+                &mut [],
+            )
+        });
+        let code = output.finish(result).unwrap();
+        (code, reader.join().unwrap())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_to_cat() {
+        let (code, captured) = run_through_command("cat", 1000);
+        assert_eq!(code, 0);
+        assert_eq!(
+            captured,
+            rdgen_lib::FiniteDataWriter::new("abc", Some(1000))
+                .flatten()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_to_early_exit() {
+        // head exits after 10 bytes, so writing the rest fails with a broken pipe
+        let (code, captured) = run_through_command("head -c 10", 10_000_000);
+        assert_eq!(code, 0);
+        assert_eq!(captured.len(), 10);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_to_exit_code() {
+        let (code, _) = run_through_command("false", 10);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn empty_command() {
+        assert!(spawn_command("  ", Stdio::null()).is_err());
+    }
+
+    #[test]
+    fn enough_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let p = dir.path().join("small.bin");
+        check_available_space(&p, 0, FileMode::Truncate).unwrap();
+        check_available_space(&p, 1, FileMode::Append).unwrap();
+    }
+
+    #[test]
+    fn impossibly_large_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let p = dir.path().join("large.bin");
+        assert!(check_available_space(&p, u64::MAX, FileMode::Truncate).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::Write;
+
+/// A writer that only passes multiples of `alignment` bytes to every write call of the inner writer,
+/// by holding back the bytes after the last boundary until the boundary after them is reached.
+/// The remaining bytes, which may not fill a whole boundary, are written by `finish`.
+pub struct Align<W: Write> {
+    inner: W,
+    alignment: usize,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Align<W> {
+    pub fn new(inner: W, alignment: usize) -> Self {
+        assert!(alignment > 0, "Alignment must be larger than zero");
+        Self {
+            inner,
+            alignment,
+            pending: Vec::with_capacity(alignment),
+        }
+    }
+
+    /// Write the remaining bytes, flush, and return the inner writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if !self.pending.is_empty() {
+            self.inner.write_all(&self.pending)?;
+            self.pending.clear();
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for Align<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.pending.is_empty() {
+            let taken = buf.len().min(self.alignment - self.pending.len());
+            self.pending.extend_from_slice(&buf[..taken]);
+            if self.pending.len() == self.alignment {
+                self.inner.write_all(&self.pending)?;
+                self.pending.clear();
+            }
+            return Ok(taken);
+        }
+
+        let aligned = buf.len() - buf.len() % self.alignment;
+        if aligned > 0 {
+            self.inner.write_all(&buf[..aligned])?;
+        }
+        self.pending.extend_from_slice(&buf[aligned..]);
+        Ok(buf.len())
+    }
+
+    /// Flushing does not write the bytes held back, so that the writes stay aligned
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the size of every write
+    #[derive(Default)]
+    struct WriteRecorder {
+        data: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl Write for &mut WriteRecorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_are_aligned() {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(10_000))
+            .flatten()
+            .collect();
+
+        for alignment in [1, 3, 64, 512, 4096] {
+            let mut recorder = WriteRecorder::default();
+            let mut writer = Align::new(&mut recorder, alignment);
+            for chunk in data.chunks(100).chain(data.chunks(777)) {
+                writer.write_all(chunk).unwrap();
+                writer.flush().unwrap();
+            }
+            writer.finish().unwrap();
+
+            let (last, aligned) = recorder.writes.split_last().unwrap();
+            assert!(aligned.iter().all(|w| w % alignment == 0));
+            assert!(*last > 0);
+            assert_eq!(recorder.data, [&data[..], &data[..]].concat());
+        }
+    }
+}
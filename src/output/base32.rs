@@ -0,0 +1,112 @@
+use std::io::Write;
+
+use super::Encoder;
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const GROUP_SIZE: usize = 5;
+
+/// Base32, with the alphabet and padding of RFC 4648. The encoding streams: every group of 5 bytes is written
+/// out as 8 characters as soon as it is complete, and the last, possibly partial and padded, group and
+/// a newline are written by `finalize`.
+pub struct Base32 {
+    group: Vec<u8>,
+}
+
+impl Base32 {
+    pub fn new() -> Self {
+        Self {
+            group: Vec::with_capacity(GROUP_SIZE),
+        }
+    }
+
+    fn write_group(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut bytes = [0; GROUP_SIZE];
+        bytes[..self.group.len()].copy_from_slice(&self.group);
+        let bits = bytes.iter().fold(0u64, |bits, &b| bits << 8 | b as u64);
+
+        // Every character encodes 5 bits, and the characters that encode no bits of the data are padding
+        let characters = (self.group.len() * 8).div_ceil(5);
+        let mut encoded = [b'='; 8];
+        for (i, c) in encoded.iter_mut().enumerate().take(characters) {
+            *c = ALPHABET[(bits >> (35 - 5 * i) & 0x1f) as usize];
+        }
+
+        out.write_all(&encoded)?;
+        self.group.clear();
+        Ok(())
+    }
+}
+
+impl Encoder for Base32 {
+    fn encode_chunk(&mut self, mut chunk: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        while !chunk.is_empty() {
+            let taken = chunk.len().min(GROUP_SIZE - self.group.len());
+            self.group.extend_from_slice(&chunk[..taken]);
+            chunk = &chunk[taken..];
+            if self.group.len() == GROUP_SIZE {
+                self.write_group(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if !self.group.is_empty() {
+            self.write_group(out)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::encoder::encode_all;
+
+    fn encode(data: &[u8]) -> String {
+        String::from_utf8(encode_all(Base32::new(), &[data])).unwrap()
+    }
+
+    fn decode(text: &str) -> Vec<u8> {
+        let mut bits = 0u64;
+        let mut bit_count = 0;
+        let mut data = Vec::new();
+        for c in text.trim_end().bytes().take_while(|&c| c != b'=') {
+            let value = ALPHABET.iter().position(|&a| a == c).unwrap() as u64;
+            bits = bits << 5 | value;
+            bit_count += 5;
+            if bit_count >= 8 {
+                bit_count -= 8;
+                data.push((bits >> bit_count) as u8);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn rfc_4648_vectors() {
+        for (data, expected) in [
+            ("", ""),
+            ("f", "MY======"),
+            ("fo", "MZXQ===="),
+            ("foo", "MZXW6==="),
+            ("foob", "MZXW6YQ="),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI======"),
+        ] {
+            assert_eq!(encode(data.as_bytes()), format!("{expected}\n"));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(203))
+            .flatten()
+            .collect();
+        for length in [0, 1, 4, 5, 6, 100, 203] {
+            assert_eq!(decode(&encode(&data[..length])), data[..length]);
+        }
+        let chunked = encode_all(Base32::new(), &[&data[..3], &data[3..4], &data[4..]]);
+        assert_eq!(String::from_utf8(chunked).unwrap(), encode(&data));
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::Write;
+
+use super::Encoder;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The number of bytes of every record, which is encoded on its own line
+pub const RECORD_SIZE: usize = 32;
+
+/// Base58, with the Bitcoin alphabet, where every leading zero byte is encoded as `1`. Since base58 encodes
+/// its input as a single number, it cannot stream: the data is split into records of 32 bytes, and every
+/// record is buffered and then written on its own line. The last, possibly partial, record is written
+/// by `finalize`.
+pub struct Base58 {
+    record: Vec<u8>,
+}
+
+impl Base58 {
+    pub fn new() -> Self {
+        Self {
+            record: Vec::with_capacity(RECORD_SIZE),
+        }
+    }
+
+    fn write_record(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut line = encode(&self.record);
+        line.push(b'\n');
+        out.write_all(&line)?;
+        self.record.clear();
+        Ok(())
+    }
+}
+
+/// Encode the given bytes as base58, by repeated division of the big-endian number they represent
+fn encode(data: &[u8]) -> Vec<u8> {
+    let leading_zeros = data.iter().take_while(|&&b| b == 0).count();
+
+    // The digits in base 58, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[leading_zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut encoded = vec![ALPHABET[0]; leading_zeros];
+    encoded.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize]));
+    encoded
+}
+
+impl Encoder for Base58 {
+    fn encode_chunk(&mut self, mut chunk: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        while !chunk.is_empty() {
+            let taken = chunk.len().min(RECORD_SIZE - self.record.len());
+            self.record.extend_from_slice(&chunk[..taken]);
+            chunk = &chunk[taken..];
+            if self.record.len() == RECORD_SIZE {
+                self.write_record(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if !self.record.is_empty() {
+            self.write_record(out)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::encoder::encode_all;
+
+    fn decode(text: &str) -> Vec<u8> {
+        let leading_ones = text.bytes().take_while(|&c| c == ALPHABET[0]).count();
+
+        // The bytes, least significant first
+        let mut bytes: Vec<u8> = Vec::new();
+        for c in text[leading_ones..].bytes() {
+            let mut carry = ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+            for byte in bytes.iter_mut() {
+                carry += *byte as u32 * 58;
+                *byte = carry as u8;
+                carry >>= 8;
+            }
+            while carry > 0 {
+                bytes.push(carry as u8);
+                carry >>= 8;
+            }
+        }
+
+        let mut decoded = vec![0; leading_ones];
+        decoded.extend(bytes.into_iter().rev());
+        decoded
+    }
+
+    #[test]
+    fn known_vectors() {
+        for (data, expected) in [
+            (&b""[..], ""),
+            (b"\0", "1"),
+            (b"\0\0\x01", "112"),
+            (b"hello world", "StV1DL6CwTryKyV"),
+        ] {
+            assert_eq!(encode(data), expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(100))
+            .flatten()
+            .collect();
+        let text =
+            String::from_utf8(encode_all(Base58::new(), &[&data[..10], &data[10..]])).unwrap();
+
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        let decoded: Vec<u8> = lines.iter().flat_map(|line| decode(line)).collect();
+        assert_eq!(decoded, data);
+    }
+}
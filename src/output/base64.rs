@@ -0,0 +1,142 @@
+use std::io::Write;
+
+use super::Encoder;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const GROUP_SIZE: usize = 3;
+
+/// Base64, with the standard alphabet and padding of RFC 4648, wrapped in lines of a fixed number of columns,
+/// as in MIME and PEM. The encoding streams: every group of 3 bytes is written out as 4 characters as soon as
+/// it is complete, and the last, possibly partial and padded, group and a newline are written by `finalize`.
+pub struct Base64 {
+    group: Vec<u8>,
+    columns: usize,
+    column: usize,
+}
+
+impl Base64 {
+    /// Wrap the lines at the given number of columns, which must not be zero
+    pub fn wrapped(columns: usize) -> Self {
+        assert!(columns > 0);
+        Self {
+            group: Vec::with_capacity(GROUP_SIZE),
+            columns,
+            column: 0,
+        }
+    }
+
+    fn write_group(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut bytes = [0; GROUP_SIZE];
+        bytes[..self.group.len()].copy_from_slice(&self.group);
+        let bits = bytes.iter().fold(0u32, |bits, &b| bits << 8 | b as u32);
+
+        // Every character encodes 6 bits, and the characters that encode no bits of the data are padding
+        let characters = self.group.len() + 1;
+        let mut encoded = [b'='; 4];
+        for (i, c) in encoded.iter_mut().enumerate().take(characters) {
+            *c = ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize];
+        }
+
+        // A line is ended just before the next character, so that the output never ends with an empty line
+        let mut encoded = &encoded[..];
+        while !encoded.is_empty() {
+            if self.column == self.columns {
+                out.write_all(b"\n")?;
+                self.column = 0;
+            }
+            let length = encoded.len().min(self.columns - self.column);
+            out.write_all(&encoded[..length])?;
+            self.column += length;
+            encoded = &encoded[length..];
+        }
+        self.group.clear();
+        Ok(())
+    }
+}
+
+impl Encoder for Base64 {
+    fn encode_chunk(&mut self, mut chunk: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        while !chunk.is_empty() {
+            let taken = chunk.len().min(GROUP_SIZE - self.group.len());
+            self.group.extend_from_slice(&chunk[..taken]);
+            chunk = &chunk[taken..];
+            if self.group.len() == GROUP_SIZE {
+                self.write_group(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if !self.group.is_empty() {
+            self.write_group(out)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::encoder::encode_all;
+
+    fn encode(data: &[u8], columns: usize) -> String {
+        String::from_utf8(encode_all(Base64::wrapped(columns), &[data])).unwrap()
+    }
+
+    fn decode(text: &str) -> Vec<u8> {
+        let mut bits = 0u32;
+        let mut bit_count = 0;
+        let mut data = Vec::new();
+        for c in text
+            .bytes()
+            .filter(|&c| c != b'\n')
+            .take_while(|&c| c != b'=')
+        {
+            let value = ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+            bits = bits << 6 | value;
+            bit_count += 6;
+            if bit_count >= 8 {
+                bit_count -= 8;
+                data.push((bits >> bit_count) as u8);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn rfc_4648_vectors() {
+        for (data, expected) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(data.as_bytes(), 76), format!("{expected}\n"));
+        }
+        assert_eq!(encode(b"foobar", 3), "Zm9\nvYm\nFy\n");
+        assert_eq!(encode(b"foobar", 4), "Zm9v\nYmFy\n");
+    }
+
+    #[test]
+    fn wrapped_round_trip() {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(1000))
+            .flatten()
+            .collect();
+        for columns in [1, 4, 64, 76, 100] {
+            for length in [0, 1, 2, 3, 47, 48, 57, 100, 1000] {
+                let text = encode(&data[..length], columns);
+                assert!(text.ends_with('\n'));
+                assert!(text.lines().all(|l| !l.is_empty() || length == 0));
+                assert!(text.lines().all(|l| l.len() <= columns));
+                assert!(text.lines().rev().skip(1).all(|l| l.len() == columns));
+                assert_eq!(decode(&text), data[..length]);
+            }
+        }
+        let chunked = encode_all(Base64::wrapped(76), &[&data[..1], &data[1..5], &data[5..]]);
+        assert_eq!(String::from_utf8(chunked).unwrap(), encode(&data, 76));
+    }
+}
@@ -0,0 +1,105 @@
+use std::{io::Write, time::Duration};
+
+/// A pattern of bursts, given as `ON_BYTES:OFF_MS` in the command line: `on_bytes` are written,
+/// then the output pauses for `off`, and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Burst {
+    on_bytes: u64,
+    off: Duration,
+}
+
+impl std::str::FromStr for Burst {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (on_bytes, off) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected ON_BYTES:OFF_MS, found: {s}"))?;
+        let on_bytes: u64 = on_bytes
+            .parse()
+            .map_err(|e| format!("Invalid byte count: {e}"))?;
+        if on_bytes == 0 {
+            return Err("The byte count of a burst must be positive".to_string());
+        }
+        let off: u64 = off.parse().map_err(|e| format!("Invalid pause: {e}"))?;
+        Ok(Self {
+            on_bytes,
+            off: Duration::from_millis(off),
+        })
+    }
+}
+
+/// A writer that writes the data in bursts. After every burst, everything written so far is flushed,
+/// and the writer pauses before writing the next byte. There is no pause after the last byte.
+pub struct Bursty<W: Write> {
+    inner: W,
+    burst: Burst,
+    remaining_in_burst: u64,
+}
+
+impl<W: Write> Bursty<W> {
+    pub fn new(inner: W, burst: Burst) -> Self {
+        Self {
+            inner,
+            burst,
+            remaining_in_burst: burst.on_bytes,
+        }
+    }
+}
+
+impl<W: Write> Write for Bursty<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining_in_burst == 0 {
+            self.inner.flush()?;
+            std::thread::sleep(self.burst.off);
+            self.remaining_in_burst = self.burst.on_bytes;
+        }
+
+        let max_length = self.remaining_in_burst.min(buf.len() as u64) as usize;
+        let written = self.inner.write(&buf[..max_length])?;
+        self.remaining_in_burst -= written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::time_recorder::TimeRecorder;
+
+    #[test]
+    fn parsing() {
+        assert_eq!(
+            "100:50".parse::<Burst>().unwrap(),
+            Burst {
+                on_bytes: 100,
+                off: Duration::from_millis(50)
+            }
+        );
+        for invalid in ["", "100", "0:50", "a:50", "100:a", "100:-1", "100:50:1"] {
+            assert!(invalid.parse::<Burst>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn pauses_between_bursts() {
+        let data: Vec<u8> = (0..250).map(|i| i as u8).collect();
+        let mut recorder = TimeRecorder::new();
+        let mut bursty = Bursty::new(&mut recorder, "100:100".parse().unwrap());
+        bursty.write_all(&data).unwrap();
+        bursty.flush().unwrap();
+
+        assert_eq!(recorder.data, data);
+        recorder.assert_delayed_at(&[
+            (100, Duration::from_millis(100)),
+            (200, Duration::from_millis(200)),
+        ]);
+    }
+}
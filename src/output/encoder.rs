@@ -0,0 +1,82 @@
+use std::io::Write;
+
+/// An encoding of the generated data, which streams: every chunk is encoded as soon as it is given, except
+/// for the bytes that the encoding has to buffer, such as a partial group, which are encoded by `finalize`.
+pub trait Encoder {
+    fn encode_chunk(&mut self, chunk: &[u8], out: &mut impl Write) -> std::io::Result<()>;
+
+    /// Encode what is left after the last chunk, and write the end of the encoding, if it has one
+    fn finalize(self, out: &mut impl Write) -> std::io::Result<()>;
+}
+
+/// A writer that encodes the data written to it with the given encoder. After writing,
+/// the encoding must be finished with `finish`.
+pub struct Encoding<E: Encoder, W: Write> {
+    encoder: E,
+    inner: W,
+}
+
+impl<E: Encoder, W: Write> Encoding<E, W> {
+    pub fn new(encoder: E, inner: W) -> Self {
+        Self { encoder, inner }
+    }
+
+    /// Finalize the encoding, and return the inner writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.encoder.finalize(&mut self.inner)?;
+        Ok(self.inner)
+    }
+}
+
+impl<E: Encoder, W: Write> Write for Encoding<E, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.encoder.encode_chunk(buf, &mut self.inner)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Lowercase hex, two characters per byte, followed by a newline
+pub struct Hex;
+
+impl Encoder for Hex {
+    fn encode_chunk(&mut self, chunk: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(hex::encode(chunk).as_bytes())
+    }
+
+    fn finalize(self, out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(b"\n")
+    }
+}
+
+/// Encode all the data at once with the given encoder, for tests
+#[cfg(test)]
+pub fn encode_all(encoder: impl Encoder, chunks: &[&[u8]]) -> Vec<u8> {
+    let mut encoding = Encoding::new(encoder, Vec::new());
+    for chunk in chunks {
+        encoding.write_all(chunk).unwrap();
+    }
+    encoding.finish().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_vector() {
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(100))
+            .flatten()
+            .collect();
+        let expected = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd400992366cb547665e462bbdd51d9b6ce1221116e9cfc6711c78d8798158349d12fa8ca513efb14\n";
+        assert_eq!(encode_all(Hex, &[&data]), expected.as_bytes());
+        assert_eq!(
+            encode_all(Hex, &[&data[..1], &data[1..63], &data[63..]]),
+            expected.as_bytes()
+        );
+        assert_eq!(encode_all(Hex, &[]), b"\n");
+    }
+}
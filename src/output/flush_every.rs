@@ -0,0 +1,97 @@
+use std::io::Write;
+
+/// A writer that flushes the inner writer every time the given number of bytes has been written.
+/// Writes are split at the flush boundaries, so flushes happen exactly every `interval` bytes.
+pub struct FlushEvery<W: Write> {
+    inner: W,
+    interval: usize,
+    since_last_flush: usize,
+}
+
+impl<W: Write> FlushEvery<W> {
+    pub fn new(inner: W, interval: usize) -> Self {
+        assert!(interval > 0, "Flush interval must be larger than zero");
+        Self {
+            inner,
+            interval,
+            since_last_flush: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for FlushEvery<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let max_len = self.interval - self.since_last_flush;
+        let written = self.inner.write(&buf[..buf.len().min(max_len)])?;
+
+        self.since_last_flush += written;
+        if self.since_last_flush == self.interval {
+            self.inner.flush()?;
+            self.since_last_flush = 0;
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.since_last_flush = 0;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the total number of bytes written at every flush
+    #[derive(Default)]
+    struct FlushRecorder {
+        written: usize,
+        flushed_at: Vec<usize>,
+    }
+
+    impl Write for &mut FlushRecorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushed_at.push(self.written);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flushes_at_interval() {
+        let mut recorder = FlushRecorder::default();
+        {
+            let mut writer = FlushEvery::new(&mut recorder, 100);
+            for _ in 0..20 {
+                writer.write_all(&[0; 64]).unwrap();
+            }
+        }
+
+        assert_eq!(recorder.written, 1280);
+        assert_eq!(
+            recorder.flushed_at,
+            (1..=12).map(|i| i * 100).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn large_writes_are_split() {
+        let mut recorder = FlushRecorder::default();
+        {
+            let mut writer = FlushEvery::new(&mut recorder, 10);
+            writer.write_all(&[0; 35]).unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert_eq!(recorder.flushed_at, vec![10, 20, 30, 35]);
+    }
+}
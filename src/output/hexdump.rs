@@ -0,0 +1,109 @@
+use std::io::Write;
+
+use super::Encoder;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// A hexdump, in the default format of `xxd`: 16 bytes per line, an offset of (at least) 8 hex digits,
+/// the bytes in groups of two, and an ASCII sidebar, where non-printable bytes are shown as dots.
+/// Lines are written out as soon as they are complete, and the last, possibly partial, line is written
+/// by `finalize`.
+pub struct HexDump {
+    line: Vec<u8>,
+    offset: u64,
+}
+
+impl HexDump {
+    pub fn new() -> Self {
+        Self {
+            line: Vec::with_capacity(BYTES_PER_LINE),
+            offset: 0,
+        }
+    }
+
+    fn write_line(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut formatted = format!("{:08x}:", self.offset);
+        for (i, byte) in self.line.iter().enumerate() {
+            if i % 2 == 0 {
+                formatted.push(' ');
+            }
+            formatted.push_str(&format!("{byte:02x}"));
+        }
+
+        // Partial lines are padded, so that the sidebar stays aligned
+        let missing = BYTES_PER_LINE - self.line.len();
+        let padding = missing * 2 + missing / 2;
+        formatted.push_str(&" ".repeat(padding + 2));
+
+        formatted.extend(self.line.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        formatted.push('\n');
+
+        out.write_all(formatted.as_bytes())?;
+        self.offset += self.line.len() as u64;
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl Encoder for HexDump {
+    fn encode_chunk(&mut self, mut chunk: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        while !chunk.is_empty() {
+            let taken = chunk.len().min(BYTES_PER_LINE - self.line.len());
+            self.line.extend_from_slice(&chunk[..taken]);
+            chunk = &chunk[taken..];
+            if self.line.len() == BYTES_PER_LINE {
+                self.write_line(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if !self.line.is_empty() {
+            self.write_line(out)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::encoder::encode_all;
+
+    fn hexdump(data: &[u8], write_size: usize) -> String {
+        let chunks: Vec<&[u8]> = data.chunks(write_size).collect();
+        String::from_utf8(encode_all(HexDump::new(), &chunks)).unwrap()
+    }
+
+    #[test]
+    fn matches_xxd() {
+        // echo -n "abc" | rdgen -l40 | xxd
+        let expected = "\
+00000000: ba80 a53f 981c 4d0d 6a27 97b6 9f12 f6e9  ...?..M.j'......
+00000010: 4c21 2f14 685a c4b7 4b12 bb6f dbff a2d1  L!/.hZ..K..o....
+00000020: 7d87 c539 2aab 792d                      }..9*.y-
+";
+        let data: Vec<u8> = rdgen_lib::FiniteDataWriter::new("abc", Some(40))
+            .flatten()
+            .collect();
+        for write_size in [1, 7, 16, 40] {
+            assert_eq!(hexdump(&data, write_size), expected);
+        }
+    }
+
+    #[test]
+    fn odd_partial_line() {
+        assert_eq!(
+            hexdump(b"Hello, world!\n\x00", 64),
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 00    Hello, world!..\n"
+        );
+        assert_eq!(hexdump(b"", 64), "");
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, AtomicI32, Ordering},
+};
+
+/// The signal that interrupted the output, or 0 if there was none, which is set by the signal handlers
+static INTERRUPTED: AtomicI32 = AtomicI32::new(0);
+
+/// Whether an `Interruptible` writer was created, which stops at the signal. Before that, nothing
+/// would stop, so the signal stops the program as if no handler was installed.
+static ARMED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    if !ARMED.load(Ordering::SeqCst) {
+        // SAFETY: signal and raise are async-signal-safe
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        return;
+    }
+
+    // Only an atomic swap and _exit, which are safe in a signal handler
+    if INTERRUPTED.swap(signal, Ordering::SeqCst) != 0 {
+        // SAFETY: _exit is async-signal-safe
+        unsafe { libc::_exit(128 + signal) };
+    }
+}
+
+/// Install the handlers of SIGINT and SIGTERM, which stop `Interruptible` writers at their next write.
+/// A second signal exits right away, for when the output is stuck and the writers are never called again.
+/// This is installed once at the start of the program, and until a writer is created, the signals stop
+/// the program right away, as without the handlers.
+pub fn install_interrupt_signals() -> std::io::Result<()> {
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only uses atomics, signal, raise and _exit, which are async-signal-safe
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// The error of the writes of an `Interruptible` writer after a signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupted {
+    pub signal: i32,
+}
+
+impl Interrupted {
+    /// The conventional exit code of a program stopped by the signal: 130 for SIGINT, and 143 for SIGTERM
+    pub const fn exit_code(&self) -> u8 {
+        (128 + self.signal) as u8
+    }
+}
+
+impl std::fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Interrupted by signal {}", self.signal)
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+/// The interruption that caused the given error, if any
+pub fn interruption(e: &anyhow::Error) -> Option<Interrupted> {
+    e.chain().find_map(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()?
+            .get_ref()?
+            .downcast_ref::<Interrupted>()
+            .copied()
+    })
+}
+
+/// A writer that stops writing once SIGINT or SIGTERM is received. Everything written before the signal
+/// is flushed, and the writes after it fail with `Interrupted`, so that the output ends cleanly at a chunk
+/// boundary instead of with bytes still in its buffers.
+pub struct Interruptible<W: Write> {
+    inner: W,
+    flag: &'static AtomicI32,
+}
+
+impl<W: Write> Interruptible<W> {
+    pub fn new(inner: W) -> Self {
+        ARMED.store(true, Ordering::SeqCst);
+        Self {
+            inner,
+            flag: &INTERRUPTED,
+        }
+    }
+}
+
+impl<W: Write> Write for Interruptible<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.flag.load(Ordering::SeqCst) {
+            0 => self.inner.write(buf),
+            signal => {
+                self.inner.flush()?;
+                Err(std::io::Error::other(Interrupted { signal }))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+    use crate::{errors::CliError, exit_codes};
+
+    #[test]
+    fn interrupt_flushes_and_stops() {
+        // The interrupt is simulated with a flag of the test, so other tests writing output are not stopped
+        static FLAG: AtomicI32 = AtomicI32::new(0);
+        let mut writer = Interruptible {
+            inner: std::io::BufWriter::new(Vec::new()),
+            flag: &FLAG,
+        };
+        writer.write_all(b"before").unwrap();
+        assert!(writer.inner.get_ref().is_empty());
+
+        FLAG.store(libc::SIGINT, Ordering::SeqCst);
+        let e = writer.write_all(b" after").unwrap_err();
+        assert_eq!(writer.inner.get_ref(), b"before");
+
+        let e = Err::<(), _>(e)
+            .context("Writing result to output failed")
+            .map_err(CliError::output)
+            .unwrap_err();
+        let interrupted = interruption(&e).unwrap();
+        assert_eq!(interrupted.signal, libc::SIGINT);
+        assert_eq!(crate::errors::exit_code(&e), 130);
+        assert_eq!(interruption(&anyhow::anyhow!("Other")), None);
+        assert_eq!(
+            crate::errors::exit_code(&CliError::output(anyhow::anyhow!("Disk full"))),
+            exit_codes::OUTPUT
+        );
+    }
+}
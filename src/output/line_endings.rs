@@ -0,0 +1,52 @@
+use std::io::Write;
+
+/// A writer for text that writes every line ending (`\n`) as `\r\n` when CRLF line endings are enabled,
+/// and writes the text unchanged otherwise.
+pub struct LineEndings<W: Write> {
+    inner: W,
+    crlf: bool,
+}
+
+impl<W: Write> LineEndings<W> {
+    pub fn new(inner: W, crlf: bool) -> Self {
+        Self { inner, crlf }
+    }
+}
+
+impl<W: Write> Write for LineEndings<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.crlf {
+            return self.inner.write(buf);
+        }
+        for (i, line) in buf.split(|&b| b == b'\n').enumerate() {
+            if i > 0 {
+                self.inner.write_all(b"\r\n")?;
+            }
+            self.inner.write_all(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(text: &[u8], crlf: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        LineEndings::new(&mut out, crlf).write_all(text).unwrap();
+        out
+    }
+
+    #[test]
+    fn converts_line_endings() {
+        assert_eq!(convert(b"a\nbc\n\nd", true), b"a\r\nbc\r\n\r\nd");
+        assert_eq!(convert(b"\n", true), b"\r\n");
+        assert_eq!(convert(b"", true), b"");
+        assert_eq!(convert(b"a\nbc\n\nd", false), b"a\nbc\n\nd");
+    }
+}
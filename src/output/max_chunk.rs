@@ -0,0 +1,71 @@
+use std::io::Write;
+
+/// A writer that passes at most `max_chunk` bytes to every write call of the inner writer,
+/// for sinks that fail on large writes.
+pub struct MaxChunk<W: Write> {
+    inner: W,
+    max_chunk: usize,
+}
+
+impl<W: Write> MaxChunk<W> {
+    pub fn new(inner: W, max_chunk: usize) -> Self {
+        assert!(max_chunk > 0, "Maximum chunk size must be larger than zero");
+        Self { inner, max_chunk }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for MaxChunk<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write(&buf[..buf.len().min(self.max_chunk)])
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufWriter;
+
+    use super::*;
+
+    /// Records the size of the largest write
+    #[derive(Default)]
+    struct WriteRecorder {
+        written: usize,
+        max_write: usize,
+    }
+
+    impl Write for &mut WriteRecorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written += buf.len();
+            self.max_write = self.max_write.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_never_exceed_cap() {
+        let mut recorder = WriteRecorder::default();
+        {
+            // Writes larger than the buffer capacity bypass the buffer
+            let mut writer = BufWriter::with_capacity(1000, MaxChunk::new(&mut recorder, 100));
+            writer.write_all(&[0; 64]).unwrap();
+            writer.write_all(&[0; 5000]).unwrap();
+            writer.write_all(&[0; 999]).unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert_eq!(recorder.written, 6063);
+        assert_eq!(recorder.max_write, 100);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+/// A schedule of when bytes of the output may be written: a list of points `(offset, time)`, meaning that
+/// the byte at `offset` must not be written before `time` has passed since the start of the output.
+///
+/// In its text format, every line has a point as a byte offset and a time in seconds (which may be fractional),
+/// separated by whitespace. Both must be non-decreasing from line to line. Empty lines and lines starting
+/// with `#` are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingProfile {
+    points: Vec<(u64, Duration)>,
+}
+
+impl TimingProfile {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut points: Vec<(u64, Duration)> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                |reason: &str| anyhow::anyhow!("Invalid line {} of profile: {reason}", i + 1);
+
+            let mut parts = line.split_whitespace();
+            let (offset, time) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(offset), Some(time), None) => (offset, time),
+                _ => return Err(invalid("expected an offset and a time")),
+            };
+            let offset: u64 = offset.parse().map_err(|_| invalid("invalid offset"))?;
+            let time = time
+                .parse()
+                .ok()
+                .and_then(|t| Duration::try_from_secs_f64(t).ok())
+                .ok_or_else(|| invalid("invalid time"))?;
+
+            if let Some((last_offset, last_time)) = points.last() {
+                if offset < *last_offset || time < *last_time {
+                    return Err(invalid("offsets and times must not decrease"));
+                }
+            }
+            points.push((offset, time));
+        }
+
+        Ok(Self { points })
+    }
+}
+
+/// A writer that paces the data written to it according to a timing profile. Before waiting for a point
+/// of the profile, everything written so far is flushed, so that it reaches the destination on time.
+pub struct Paced<W: Write> {
+    inner: W,
+    profile: TimingProfile,
+    next_point: usize,
+    start: Instant,
+    offset: u64,
+}
+
+impl<W: Write> Paced<W> {
+    /// Start pacing, where the times of the profile are relative to now
+    pub fn new(inner: W, profile: TimingProfile) -> Self {
+        Self {
+            inner,
+            profile,
+            next_point: 0,
+            start: Instant::now(),
+            offset: 0,
+        }
+    }
+
+    /// Wait for all the points at the current offset
+    fn wait_for_points(&mut self) -> std::io::Result<()> {
+        while let Some(&(offset, time)) = self.profile.points.get(self.next_point) {
+            if offset > self.offset {
+                break;
+            }
+            let elapsed = self.start.elapsed();
+            if time > elapsed {
+                self.inner.flush()?;
+                std::thread::sleep(time - elapsed);
+            }
+            self.next_point += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Paced<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.wait_for_points()?;
+
+        // Only the bytes up to the next point can be written now
+        let max_length = match self.profile.points.get(self.next_point) {
+            Some(&(offset, _)) => (offset - self.offset).min(buf.len() as u64) as usize,
+            None => buf.len(),
+        };
+        let written = self.inner.write(&buf[..max_length])?;
+        self.offset += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::time_recorder::TimeRecorder;
+
+    #[test]
+    fn parsing() {
+        let profile =
+            TimingProfile::parse("# offset seconds\n0 0\n\n100 0.5\n  200   1.25 \n").unwrap();
+        assert_eq!(
+            profile.points,
+            [
+                (0, Duration::ZERO),
+                (100, Duration::from_millis(500)),
+                (200, Duration::from_millis(1250))
+            ]
+        );
+
+        for invalid in [
+            "100",
+            "100 1 2",
+            "a 1",
+            "100 -1",
+            "100 x",
+            "100 1\n50 2",
+            "100 2\n200 1",
+        ] {
+            assert!(TimingProfile::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn scheduled_delays() {
+        let profile = TimingProfile::parse("100 0.1\n200 0.3\n250 0.3").unwrap();
+        let mut recorder = TimeRecorder::new();
+        let mut paced = Paced::new(&mut recorder, profile);
+        paced.write_all(&[0; 300]).unwrap();
+
+        assert_eq!(recorder.written_at.len(), 300);
+        recorder.assert_delayed_at(&[
+            (100, Duration::from_millis(100)),
+            (200, Duration::from_millis(300)),
+        ]);
+    }
+}
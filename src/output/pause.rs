@@ -0,0 +1,104 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+/// Whether the output is paused, which is set by the signal handlers
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// How often a paused writer checks whether it was resumed
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only an atomic store, which is safe in a signal handler
+    PAUSED.store(signal == libc::SIGUSR1, Ordering::SeqCst);
+}
+
+/// Install the handlers of SIGUSR1, which pauses the output of `Pausable` writers, and SIGUSR2,
+/// which resumes it.
+pub fn install_pause_signals() -> std::io::Result<()> {
+    for signal in [libc::SIGUSR1, libc::SIGUSR2] {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// A writer that stops writing while the output is paused by SIGUSR1, until it is resumed by SIGUSR2.
+/// Everything written before the pause is flushed when the pause starts, and nothing is lost or changed.
+pub struct Pausable<W: Write> {
+    inner: W,
+    flag: &'static AtomicBool,
+}
+
+impl<W: Write> Pausable<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            flag: &PAUSED,
+        }
+    }
+}
+
+impl<W: Write> Write for Pausable<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.flag.load(Ordering::SeqCst) {
+            self.inner.flush()?;
+            while self.flag.load(Ordering::SeqCst) {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// A writer that can be inspected from another thread
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pause_toggles_writing() {
+        // The signals are simulated with a flag of the test, so other tests writing output are not paused
+        static FLAG: AtomicBool = AtomicBool::new(false);
+        let shared = Shared::default();
+        let mut writer = Pausable {
+            inner: shared.clone(),
+            flag: &FLAG,
+        };
+        writer.write_all(b"before").unwrap();
+
+        FLAG.store(true, Ordering::SeqCst);
+        let thread = std::thread::spawn(move || writer.write_all(b" after").unwrap());
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(*shared.0.lock().unwrap(), b"before");
+
+        FLAG.store(false, Ordering::SeqCst);
+        thread.join().unwrap();
+        assert_eq!(*shared.0.lock().unwrap(), b"before after");
+    }
+}
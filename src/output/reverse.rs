@@ -0,0 +1,121 @@
+use std::io::{Seek, SeekFrom, Write};
+
+use rdgen_lib::CounterDataWriter;
+
+use crate::stream::GENERATION_BUFFER_SIZE;
+
+/// Write `length` bytes of the stream of `writer`, from `start_offset` of the stream, in blocks
+/// of `GENERATION_BUFFER_SIZE` bytes, from the last block to the first, seeking in the output to put every
+/// block at its place. The result is the same as writing the blocks in order. The writer must not be limited
+/// to a length shorter than the range written.
+pub fn write_reverse(
+    writer: CounterDataWriter,
+    start_offset: u64,
+    length: u64,
+    out: &mut (impl Write + Seek),
+) -> anyhow::Result<()> {
+    let block_count = length.div_ceil(GENERATION_BUFFER_SIZE as u64);
+    write_blocks(writer, start_offset, length, (0..block_count).rev(), out)
+}
+
+/// Same as `write_reverse`, but the blocks are written from both ends of the output toward the middle,
+/// alternating between the first block not written yet and the last one: the first block, the last block,
+/// the second block, the block before the last, and so on, until they meet.
+pub fn write_converging(
+    writer: CounterDataWriter,
+    start_offset: u64,
+    length: u64,
+    out: &mut (impl Write + Seek),
+) -> anyhow::Result<()> {
+    let block_count = length.div_ceil(GENERATION_BUFFER_SIZE as u64);
+    write_blocks(
+        writer,
+        start_offset,
+        length,
+        converging_order(block_count),
+        out,
+    )
+}
+
+/// The indexes of `block_count` blocks, alternating from the start and the end toward the middle
+fn converging_order(block_count: u64) -> impl Iterator<Item = u64> {
+    (0..block_count).map(move |i| match i % 2 {
+        0 => i / 2,
+        _ => block_count - 1 - i / 2,
+    })
+}
+
+/// Write the blocks with the given indexes, in the order given, each at its place in the output
+fn write_blocks(
+    mut writer: CounterDataWriter,
+    start_offset: u64,
+    length: u64,
+    indexes: impl Iterator<Item = u64>,
+    out: &mut (impl Write + Seek),
+) -> anyhow::Result<()> {
+    let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+    let mut batch = [0; 64];
+
+    for index in indexes {
+        let offset = index * GENERATION_BUFFER_SIZE as u64;
+        let block = &mut buffer[..(length - offset).min(GENERATION_BUFFER_SIZE as u64) as usize];
+
+        writer.seek_to(start_offset + offset)?;
+        let mut filled = 0;
+        while filled < block.len() {
+            let pulled = writer.pull_into(&mut batch).min(block.len() - filled);
+            if pulled == 0 {
+                return Err(anyhow::anyhow!("The stream ended before the block"));
+            }
+            block[filled..filled + pulled].copy_from_slice(&batch[..pulled]);
+            filled += pulled;
+        }
+
+        out.seek(SeekFrom::Start(offset))?;
+        out.write_all(block)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_forward() {
+        for (start_offset, length) in [(0, 0), (0, 100), (0, 200_000), (1000, 150_001), (7, 65_536)]
+        {
+            let mut writer = CounterDataWriter::new("abc", Some(length));
+            writer.seek_to(start_offset).unwrap();
+            let forward: Vec<u8> = writer.flatten().collect();
+
+            let mut reverse = std::io::Cursor::new(Vec::new());
+            write_reverse(
+                CounterDataWriter::new("abc", None),
+                start_offset,
+                length,
+                &mut reverse,
+            )
+            .unwrap();
+            assert_eq!(reverse.into_inner(), forward);
+
+            let mut converging = std::io::Cursor::new(Vec::new());
+            write_converging(
+                CounterDataWriter::new("abc", None),
+                start_offset,
+                length,
+                &mut converging,
+            )
+            .unwrap();
+            assert_eq!(converging.into_inner(), forward);
+        }
+    }
+
+    #[test]
+    fn converging_order_meets_in_the_middle() {
+        assert_eq!(converging_order(0).count(), 0);
+        assert_eq!(converging_order(1).collect::<Vec<_>>(), [0]);
+        assert_eq!(converging_order(4).collect::<Vec<_>>(), [0, 3, 1, 2]);
+        assert_eq!(converging_order(5).collect::<Vec<_>>(), [0, 4, 1, 3, 2]);
+    }
+}
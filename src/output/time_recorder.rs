@@ -0,0 +1,47 @@
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+/// Records the bytes written to it, and the time every byte was written at, for the tests of the writers
+/// that delay their output
+pub struct TimeRecorder {
+    start: Instant,
+    pub written_at: Vec<Duration>,
+    pub data: Vec<u8>,
+}
+
+impl TimeRecorder {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            written_at: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Check that the writing was delayed at the given points `(offset, time)`: the byte at every offset was
+    /// written after its time, but the byte before it was not. The schedule is a lower bound, and the upper
+    /// bounds are only a tolerance for slow machines.
+    pub fn assert_delayed_at(&self, points: &[(usize, Duration)]) {
+        for &(offset, time) in points {
+            assert!(self.written_at[offset - 1] < time, "byte {}", offset - 1);
+            assert!(self.written_at[offset] >= time, "byte {offset}");
+        }
+        assert!(self.written_at.last().unwrap() < &Duration::from_secs(3));
+    }
+}
+
+impl Write for &mut TimeRecorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let now = self.start.elapsed();
+        self.written_at
+            .extend(std::iter::repeat(now).take(buf.len()));
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
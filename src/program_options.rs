@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::{builder::RangedU64ValueParser, ArgGroup, Parser, ValueEnum};
+use rdgen_lib::CounterWidth;
 
 #[derive(Parser, Clone, Debug, Default)]
 #[command(
@@ -6,15 +7,886 @@ use clap::Parser;
     version = env!("CARGO_PKG_VERSION"),
     long_about = "A terminal program for generating reproducible random data for testing based on a provided seed.",
     author = "TheQuantumPhysicist <https://github.com/TheQuantumPhysicist>",
+    group(ArgGroup::new("mode").multiple(false)),
+    group(ArgGroup::new("lines").args(["log_lines", "numbered_lines"]).multiple(false)),
+    group(ArgGroup::new("frames").args(["framed", "inline_timing"]).multiple(false)),
     after_help = r#"Pipe some seed into rdgen, specify the length of the output, to generate deterministic, random data, with any length you need. Example: echo -n "abc" | rdgen -l100 | xxd -p -c 0"#
 )]
 pub struct RDGenOptions {
-    /// The length of the data to be output
-    #[arg(long, short('l'), value_name("NUMBER"))]
-    pub length: usize,
+    /// The length of the data to be output. A length of 0 is a valid no-op: the seed is still read,
+    /// but no data is generated, and the program exits successfully.
+    #[arg(
+        long,
+        short('l'),
+        value_name("NUMBER"),
+        required_unless_present_any(["mode", "match_seed_length", "resume", "fill_output"])
+    )]
+    pub length: Option<u64>,
+
+    /// Pick the length of the output in the range from this length to --length, both included, from the seed,
+    /// so that different seeds give outputs of different lengths, and a seed always gives the same length.
+    /// The length is picked uniformly with a fork of the generator, so the data is the usual stream of the seed.
+    #[arg(
+        long,
+        value_name("NUMBER"),
+        requires("length"),
+        conflicts_with_all(["mode", "digest_size"])
+    )]
+    pub min_length: Option<u64>,
+
+    /// Use the size of the seed, in bytes, as the length of the output. This is only available when the seed
+    /// is given with --file or --seed, because the size of other sources is not known in advance.
+    #[arg(long, conflicts_with_all(["length", "mode"]))]
+    pub match_seed_length: bool,
+
+    /// Use the current size of the existing file or device of --output as the length of the output, and overwrite
+    /// it in place, to fill it exactly. The size must be known, so this is not available for pipes.
+    #[arg(
+        long,
+        requires("output"),
+        conflicts_with_all([
+            "length", "match_seed_length", "mode", "resume", "append", "prealloc", "prefix", "suffix",
+            "hexdump", "format", "base64_wrap", "shard", "pipe_to"
+        ])
+    )]
+    pub fill_output: bool,
 
     /// An optional path of the source file to read, in case you do not want to use stdin.
     /// If not provided, the program expects to get the seed from stdin.
     #[arg(long, short('f'))]
     pub file: Option<std::path::PathBuf>,
+
+    /// Use only the given line of the file of --file as the seed, where the first line is 1,
+    /// without its line ending (\n or \r\n).
+    #[arg(
+        long,
+        value_name("N"),
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..),
+        requires("file"),
+        conflicts_with("match_seed_length")
+    )]
+    pub seed_line: Option<u64>,
+
+    /// The seed, given directly in the command line, instead of reading it from stdin or from a file
+    #[arg(long, short('s'), conflicts_with("file"))]
+    pub seed: Option<String>,
+
+    /// Read the seed from stdin only up to the first occurrence of the given delimiter, which is not part of
+    /// the seed, and leave the rest of stdin as the data of --xor-file, --compare-stream, --compare-hashes
+    /// or --prefix-match, when their path is "-"
+    #[arg(long, value_name("BYTES"), conflicts_with_all(["file", "seed"]))]
+    pub seed_delimiter: Option<String>,
+
+    /// Download the seed from the given URL, instead of reading it from stdin or from a file
+    #[cfg(feature = "seed-url")]
+    #[arg(long, value_name("URL"), conflicts_with_all(["file", "seed", "seed_delimiter"]))]
+    pub seed_url: Option<String>,
+
+    /// Use the seed, which must then be exactly 64 bytes, directly as the internal seed of the generator,
+    /// instead of its Blake2b hash. For users who derive the seed themselves.
+    #[arg(long)]
+    pub no_seed_hash: bool,
+
+    /// Append the given string to the seed, whatever its source, before it is hashed, for example
+    /// to tie the data to a build with `--seed-suffix "$(git rev-parse HEAD)"`. The suffix is part of the seed,
+    /// so the output is the same as with a seed that ends with the suffix. This differs from
+    /// `InfiniteDataWriter::mix_in` of the library, which mixes bytes into the seed after it is hashed.
+    #[arg(long, value_name("STRING"), conflicts_with("no_seed_hash"))]
+    pub seed_suffix: Option<String>,
+
+    /// Fail if the seed is empty, to catch accidental empty pipes and files. Without it, an empty seed
+    /// is valid, and its internal seed is the Blake2b hash of the empty string.
+    /// The seed is checked before --seed-suffix is appended to it.
+    #[arg(long)]
+    pub require_seed: bool,
+
+    /// Use only the first K bytes of the seed source, or all of it if it is shorter, and ignore the rest,
+    /// for example to key the data off the header of a large file. The rest of the source is never read.
+    /// This is not a safety limit, which would reject longer seeds: longer seeds are valid, and give the same
+    /// data as their first K bytes. --seed-suffix is appended after the K bytes.
+    #[arg(long, value_name("K"))]
+    pub seed_bytes: Option<u64>,
+
+    /// Mix the given bytes, in hex, into the internal seed, with `InfiniteDataWriter::mix_in`, for data
+    /// that varies from run to run, for example with `--nonce "$(openssl rand -hex 16)"`, but can still be
+    /// reproduced: the nonce is printed to stderr and written to --metadata, and the same seed and nonce
+    /// give the same data.
+    #[arg(long, value_name("HEX"))]
+    pub nonce: Option<HexBytes>,
+
+    /// An optional path of the file to write the output to, in case you do not want to use stdout.
+    /// If the file exists, it will be overwritten.
+    #[arg(long, short('o'))]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Skip the generation, with no output and exit code 0, if the given file exists and its modification time
+    /// is strictly later than the one of the seed file of --file, like a make rule. If the times are equal,
+    /// or the file does not exist, the data is generated as usual.
+    #[arg(long, value_name("OUTPUT"), requires("file"))]
+    pub if_newer: Option<std::path::PathBuf>,
+
+    /// Instead of writing to stdout, spawn the given command and write the output to its stdin.
+    /// The command is split by whitespace, without any shell processing, and its exit code becomes the exit code of rdgen.
+    #[arg(long, value_name("COMMAND"), conflicts_with("output"))]
+    pub pipe_to: Option<String>,
+
+    /// Start generating the data at this byte offset of the stream, as if the first BYTES bytes were generated
+    /// and discarded. The counter generator seeks there directly; the other generators have to compute
+    /// the skipped bytes.
+    #[arg(long, value_name("BYTES"), conflicts_with("mode"))]
+    pub start_offset: Option<u64>,
+
+    /// Append the generated data to the output file instead of overwriting it. The file must be exactly
+    /// --start-offset bytes long, so that the appended data continues the stream already in the file.
+    #[arg(long, requires("output"), requires("start_offset"))]
+    pub append: bool,
+
+    /// Truncate an existing output file before writing to it, so that nothing of its previous content is left.
+    /// This is the default, and the last of --truncate and --no-truncate wins.
+    #[arg(
+        long,
+        requires("output"),
+        overrides_with("no_truncate"),
+        conflicts_with("append")
+    )]
+    pub truncate: bool,
+
+    /// Overwrite an existing output file from its start without truncating it, so that the bytes of the file
+    /// after the end of the output are left as they are. With --append, the file is never truncated anyway.
+    #[arg(
+        long,
+        requires("output"),
+        overrides_with("truncate"),
+        conflicts_with_all(["append", "prealloc", "resume", "fs_image"])
+    )]
+    pub no_truncate: bool,
+
+    /// Before writing to the output file, make sure the target filesystem has enough free space
+    /// for the requested length, and abort early if it does not. Ignored when writing to stdout.
+    #[arg(long, requires("output"))]
+    pub check_space: bool,
+
+    /// Reserve the full size of the output file before writing to it, which avoids fragmentation and fails early
+    /// if there is not enough space. The file is then written over from the start.
+    #[arg(
+        long,
+        requires("output"),
+        conflicts_with_all(["mode", "append", "hexdump", "format", "base64_wrap"])
+    )]
+    pub prealloc: bool,
+
+    /// Write the output file from its last block to its first, seeking in the file, for cache and prefetch
+    /// tests. The file is the same as when written in order. Only available with the counter generator,
+    /// which can compute any block without the ones before it.
+    #[arg(
+        long,
+        requires("output"),
+        conflicts_with_all([
+            "mode", "prefix", "suffix", "hexdump", "format", "base64_wrap", "serial", "append", "checkpoint",
+            "prealloc", "align", "timing", "burst", "compare_stream", "compare_stdin_and_file", "assert_eq"
+        ])
+    )]
+    pub reverse: bool,
+
+    /// Write the output file from both ends toward the middle, alternating between the next block from the
+    /// start and the next block from the end, seeking in the file, for I/O pattern tests. The file is the same
+    /// as when written in order. Only available with the counter generator, like --reverse.
+    #[arg(
+        long,
+        requires("output"),
+        conflicts_with_all([
+            "mode", "prefix", "suffix", "hexdump", "format", "base64_wrap", "serial", "append", "checkpoint",
+            "prealloc", "align", "timing", "burst", "compare_stream", "compare_stdin_and_file", "assert_eq",
+            "reverse"
+        ])
+    )]
+    pub converge: bool,
+
+    /// Periodically write the state of the run to the given checkpoint file, so that an interrupted run
+    /// can be continued with --resume. The output is flushed before every checkpoint. Only available
+    /// with the chain generator and Blake2b, for plain output without --start-offset.
+    #[arg(
+        long,
+        value_name("PATH"),
+        requires("output"),
+        conflicts_with_all([
+            "mode", "prefix", "suffix", "hexdump", "format", "base64_wrap", "serial", "interleave",
+            "start_offset", "shard", "append", "align"
+        ])
+    )]
+    pub checkpoint: Option<std::path::PathBuf>,
+
+    /// The number of bytes of output between checkpoints, which must be a multiple of 64
+    #[arg(
+        long,
+        value_name("BYTES"),
+        default_value_t = 64 * 1024 * 1024,
+        value_parser = parse_checkpoint_interval,
+        requires("checkpoint")
+    )]
+    pub checkpoint_every: u64,
+
+    /// Continue an interrupted run from the given checkpoint file, written with --checkpoint. The output file
+    /// is truncated to the offset of the checkpoint, and the rest of the output is appended to it, with the
+    /// length of the checkpoint. The resumed output is the same as the one of an uninterrupted run.
+    #[arg(
+        long,
+        value_name("PATH"),
+        requires("output"),
+        conflicts_with_all([
+            "length", "match_seed_length", "mode", "prefix", "suffix", "hexdump", "format", "base64_wrap",
+            "serial", "interleave", "start_offset", "shard", "append", "align", "prealloc"
+        ])
+    )]
+    pub resume: Option<std::path::PathBuf>,
+
+    /// The method used to generate the data from the seed. Each generator produces a different stream.
+    #[arg(long, value_enum, default_value_t = Generator::Chain)]
+    pub generator: Generator,
+
+    /// The hash function used by the chain generator. Each hash function produces a different stream,
+    /// with batches of its output size (64 bytes for blake2b, 32 bytes for sha256).
+    #[arg(long, value_enum, default_value_t = HashFunction::Blake2b)]
+    pub hash: HashFunction,
+
+    /// The output size of Blake2b in the chain generator, from 1 to 64 bytes, which is the size of every batch.
+    /// Blake2b commits to its output size, so every size produces a completely different stream.
+    /// The default is 64 bytes, the stream without this option.
+    #[arg(
+        long,
+        value_name("BYTES"),
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..=64),
+        conflicts_with_all([
+            "mode", "interleave", "no_seed_hash", "checkpoint", "resume", "reverse",
+            "converge"
+        ])
+    )]
+    pub digest_size: Option<usize>,
+
+    /// The number of bytes (4, 8 or 16) used to encode the little-endian block index in the counter generator.
+    /// Changing it changes the generated stream.
+    #[arg(long, value_name("BYTES"), default_value = "8", value_parser = parse_counter_width)]
+    pub counter_width: CounterWidth,
+
+    /// Generate only the given shard of the output, where INDEX counts from 0, for generating one output
+    /// on TOTAL machines. The output of --length bytes is split into TOTAL contiguous ranges, of sizes
+    /// that differ by at most one byte, and shard INDEX is the INDEX-th range, so concatenating the shards
+    /// in order gives the whole output. Only available with the counter generator.
+    #[arg(
+        long,
+        value_name("INDEX/TOTAL"),
+        conflicts_with("mode"),
+        conflicts_with("append")
+    )]
+    pub shard: Option<Shard>,
+
+    /// Flush the output every time this number of bytes is written. The output is buffered,
+    /// so small values reduce the latency for the consumer of the data at the cost of throughput.
+    #[arg(
+        long,
+        value_name("BYTES"),
+        default_value_t = crate::output::OUTPUT_BUFFER_SIZE,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub flush_every: usize,
+
+    /// The maximum number of bytes passed to every write to the output, regardless of the internal batching,
+    /// for sinks that fail on large writes
+    #[arg(
+        long,
+        value_name("BYTES"),
+        default_value_t = crate::output::OUTPUT_BUFFER_SIZE,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_chunk: usize,
+
+    /// Make every write to the output, except possibly the last one, a multiple of N bytes,
+    /// for devices that expect writes aligned to a record boundary (e.g. 512 or 4096).
+    /// If N is larger than --max-chunk, writes of N bytes are still made.
+    #[arg(
+        long,
+        value_name("N"),
+        default_value_t = 1,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub align: usize,
+
+    /// Alternate blocks of the stream of the seed with blocks of the stream of this other seed:
+    /// first --interleave-block bytes of the stream of the seed, then as many bytes of the stream
+    /// of the other seed, and so on. Only available with the chain generator.
+    #[arg(long, value_name("OTHER_SEED"), conflicts_with("generator"))]
+    pub interleave: Option<String>,
+
+    /// The size in bytes of the blocks alternated with --interleave
+    #[arg(
+        long,
+        value_name("N"),
+        default_value_t = 64,
+        requires("interleave"),
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub interleave_block: usize,
+
+    /// Overwrite 8 bytes of the generated data, at --serial-offset, with this serial number as a little-endian u64,
+    /// to trace which run produced a file. This intentionally changes those bytes of the generated stream;
+    /// the rest of the data is unchanged.
+    #[arg(long, value_name("N"))]
+    pub serial: Option<u64>,
+
+    /// The offset in the generated data of the serial number of --serial
+    #[arg(long, value_name("BYTES"), default_value_t = 0, requires("serial"))]
+    pub serial_offset: u64,
+
+    /// Fixed bytes, in hex, written before the generated data. They are not counted in --length.
+    #[arg(long, value_name("HEX"))]
+    pub prefix: Option<HexBytes>,
+
+    /// Fixed bytes, in hex, written after the generated data. They are not counted in --length.
+    #[arg(long, value_name("HEX"))]
+    pub suffix: Option<HexBytes>,
+
+    /// End the lines of text output with CRLF (\r\n) instead of LF (\n). Only available for output
+    /// that is text: --log-lines, --numbered-lines, --kv-lines, --derive-seeds, --choices-file, --words, --ips, --datetimes,
+    /// --hexdump, --base64-wrap and every --format but raw.
+    #[arg(
+        long,
+        conflicts_with_all([
+            "xor_file", "shuffle_lines", "corrupt", "template", "utf8", "framed", "inline_timing", "fs_image",
+            "layout", "dump_states", "self_test",
+            "bench", "compare_hashes", "compare_dir", "prefix_match", "compare_stream", "compare_stdin_and_file",
+            "assert_eq"
+        ])
+    )]
+    pub crlf: bool,
+
+    /// Write the output as a hexdump, in the default format of `xxd`, with 16 bytes per line, the offset
+    /// of every line, and an ASCII sidebar. The prefix and suffix, if any, are part of the hexdump.
+    #[arg(long, conflicts_with("format"))]
+    pub hexdump: bool,
+
+    /// The encoding of the output. Every encoding but base58 streams; base58 encodes the data in records
+    /// of 32 bytes, one per line. The prefix and suffix, if any, are encoded with the data.
+    #[arg(long, value_enum, default_value_t = Format::Raw)]
+    pub format: Format,
+
+    /// Write the output as base64, with the standard alphabet and padding, wrapped in lines of the given
+    /// number of columns, for emails or PEM-like blobs. The width defaults to 76 columns, as in MIME,
+    /// when it is not given; PEM uses 64. The encoding streams, and the prefix and suffix, if any,
+    /// are encoded with the data.
+    #[arg(
+        long,
+        value_name("COLS"),
+        num_args(0..=1),
+        default_missing_value("76"),
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        conflicts_with_all(["hexdump", "format", "mode"])
+    )]
+    pub base64_wrap: Option<usize>,
+
+    /// Pace the output according to the timing profile in the given file, to replay data at the speed it was
+    /// captured. Every line of the profile has a byte offset of the output and a time in seconds since the start,
+    /// separated by whitespace, meaning that the byte at that offset is not written before that time.
+    /// Offsets and times must not decrease from line to line. Empty lines and lines starting with # are ignored.
+    #[arg(long, value_name("PROFILE"))]
+    pub timing: Option<std::path::PathBuf>,
+
+    /// Write the output in bursts, to simulate a bursty source: ON_BYTES are written, then the output pauses
+    /// for OFF_MS milliseconds, and so on. The generated bytes are unchanged.
+    #[arg(long, value_name("ON_BYTES:OFF_MS"))]
+    pub burst: Option<crate::output::Burst>,
+
+    /// Pause the output on SIGUSR1, and resume it on SIGUSR2, without losing or changing any data.
+    /// Only available on Unix.
+    #[cfg(all(unix, feature = "signals"))]
+    #[arg(long)]
+    pub pause_signals: bool,
+
+    /// After generating the data, print a summary of the run to stderr, with the total bytes written,
+    /// the wall time, the average throughput and the number of hash evaluations.
+    #[arg(long)]
+    pub stats: bool,
+
+    /// After generating the data, print the Shannon entropy of its byte frequencies, in bits per byte,
+    /// and its least and most frequent byte values to stderr, to check that the data looks uniform.
+    #[arg(long)]
+    pub entropy_report: bool,
+
+    /// After generating the data, print percentiles and a histogram of the throughput of the output to stderr,
+    /// sampled over about 100 milliseconds at a time, to check the pacing of --timing and --burst.
+    /// The data is not changed.
+    #[arg(long)]
+    pub rate_report: bool,
+
+    /// After generating the data, print the CRC-32 (IEEE) checksum of the generated data to stderr, in hex.
+    /// It is far faster than Blake2b, for integrity checks, but it does not protect against deliberate changes.
+    #[cfg(feature = "crc32")]
+    #[arg(long)]
+    pub crc32: bool,
+
+    /// After generating the data, print the XXH64 hash (with seed 0) of the generated data to stderr, in hex.
+    /// It is far faster than Blake2b, for integrity checks, but it does not protect against deliberate changes.
+    #[cfg(feature = "xxhash")]
+    #[arg(long)]
+    pub xxhash: bool,
+
+    /// Print a hexdump of the first given number of generated bytes to stderr, without --prefix, while
+    /// the output gets the complete data, unchanged
+    #[arg(long, value_name("BYTES"))]
+    pub preview: Option<u64>,
+
+    /// After a successful run, write a JSON document that describes how the output was generated to the given
+    /// path: a fingerprint of the seed, the length, the mode, the generator, the hash function, the format,
+    /// and the version of rdgen. The fingerprint identifies the seed without revealing it.
+    #[arg(
+        long,
+        value_name("PATH"),
+        conflicts_with_all([
+            "self_test", "bench", "compare_hashes", "compare_dir", "prefix_match", "compare_stream",
+            "compare_stdin_and_file", "assert_eq", "resume"
+        ])
+    )]
+    pub metadata: Option<std::path::PathBuf>,
+
+    /// After generating the data, compare the Blake2b-512 digest of the generated data (as computed by `b2sum`,
+    /// without --prefix and --suffix) with the given digest in hex. If they differ, the exit code is 3.
+    #[arg(long, value_name("HEX"))]
+    pub check_digest: Option<HexBytes>,
+
+    /// Instead of writing the generated data, compare it chunk by chunk with the reference stream in the given file
+    /// (or stdin, with "-"), e.g. written by another version of rdgen with the same options, and report the first
+    /// difference. The exit code is 3 if they differ. When the reference is read from stdin, the seed must be given
+    /// with --seed or --file.
+    #[arg(
+        long,
+        value_name("PATH"),
+        conflicts_with_all(["output", "pipe_to", "hexdump"])
+    )]
+    pub compare_stream: Option<std::path::PathBuf>,
+
+    /// Instead of writing the generated data, compare both the given file and stdin with it, in lockstep,
+    /// in a single pass, to check a source and a copy of it together, and report which of them differ and
+    /// where. The exit code is 3 if either of them differs. The seed must be given with --seed or --file.
+    #[arg(
+        long,
+        value_name("PATH"),
+        conflicts_with_all(["output", "pipe_to", "hexdump", "compare_stream", "seed_delimiter"])
+    )]
+    pub compare_stdin_and_file: Option<std::path::PathBuf>,
+
+    /// Instead of writing the data, compare it with the data of a second configuration, given as arguments
+    /// of rdgen, split by whitespace, that update the options of this run, e.g. `--assert-eq "--generator counter"`,
+    /// and report the first difference. The exit code is 3 if they differ. Both use the seed of this run,
+    /// which must be given with --seed or --file.
+    #[arg(
+        long,
+        value_name("OTHER_ARGS"),
+        allow_hyphen_values(true),
+        conflicts_with_all([
+            "output", "pipe_to", "hexdump", "compare_stream", "compare_stdin_and_file"
+        ])
+    )]
+    pub assert_eq: Option<String>,
+
+    /// Instead of writing the data, compress it with the given codec, decompress it, and compare the result
+    /// with the data, as it is generated, for testing compression libraries. The compression ratio is printed
+    /// to stderr, and the exit code is 3 if the decompressed data differs.
+    #[cfg(feature = "compression")]
+    #[arg(
+        long,
+        value_enum,
+        value_name("CODEC"),
+        conflicts_with_all([
+            "output", "pipe_to", "hexdump", "compare_stream", "compare_stdin_and_file", "assert_eq"
+        ])
+    )]
+    pub roundtrip: Option<Codec>,
+
+    /// Regenerate a set of embedded reference vectors and make sure this build reproduces them,
+    /// then exit. The exit code is non-zero if any vector does not match.
+    #[arg(long, group("mode"))]
+    pub self_test: bool,
+
+    /// Generate --bench-size bytes with every generator of this build, without writing them, and print the
+    /// throughput of every generator to stderr, then exit. No seed is read.
+    #[arg(long, group("mode"), conflicts_with_all(["output", "pipe_to"]))]
+    pub bench: bool,
+
+    /// The number of bytes generated by every generator with --bench
+    #[arg(
+        long,
+        value_name("BYTES"),
+        default_value_t = 64 * 1024 * 1024,
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..),
+        requires("bench")
+    )]
+    pub bench_size: u64,
+
+    /// Instead of generating data, count how many leading bytes of the given file match the generated stream,
+    /// and print that number. The exit code is 0 regardless of the number, which is useful to find
+    /// where a copy of generated data got corrupted.
+    #[arg(long, group("mode"), value_name("PATH"))]
+    pub prefix_match: Option<std::path::PathBuf>,
+
+    /// Instead of raw data, write the given number of deterministic pseudo-log lines.
+    /// Line `i` has the format `HH:MM:SS <payload>`, where the timestamp is `i` seconds,
+    /// and the payload is made of alphanumeric characters.
+    #[arg(long, group("mode"), value_name("COUNT"))]
+    pub log_lines: Option<u64>,
+
+    /// Instead of raw data, write the given number of numbered lines, for test logs. Line `i` has the format
+    /// `NNNNNNNN <data>`, where the line number `i` starts from 1 and is zero-padded to 8 digits, and the data
+    /// is the next --line-length bytes of the generated stream, hex-encoded.
+    #[arg(long, group("mode"), value_name("COUNT"))]
+    pub numbered_lines: Option<u64>,
+
+    /// The number of payload characters in each line of --log-lines, or of generated bytes in each line
+    /// of --numbered-lines
+    #[arg(long, value_name("NUMBER"), default_value_t = 64, requires("lines"))]
+    pub line_length: usize,
+
+    /// Instead of raw data, write the given number of `key=value` lines, for config-file fixtures. The key
+    /// starts with a letter, so that it is a valid identifier, and the rest of it and the value are alphanumeric.
+    #[arg(long, group("mode"), value_name("COUNT"))]
+    pub kv_lines: Option<u64>,
+
+    /// The number of characters of the keys of --kv-lines
+    #[arg(
+        long,
+        value_name("NUMBER"),
+        default_value_t = 8,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        requires("kv_lines")
+    )]
+    pub key_length: usize,
+
+    /// The number of characters of the values of --kv-lines
+    #[arg(
+        long,
+        value_name("NUMBER"),
+        default_value_t = 16,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        requires("kv_lines")
+    )]
+    pub value_length: usize,
+
+    /// Instead of raw data, write a copy of the file of --corrupt-input with the given number of distinct bits
+    /// flipped, at positions picked with the generated stream, for resilience tests. The same seed flips
+    /// the same bits.
+    #[arg(long, group("mode"), value_name("COUNT"), requires("corrupt_input"))]
+    pub corrupt: Option<u64>,
+
+    /// The file to write a corrupted copy of with --corrupt. It is separate from --file, which is the seed.
+    #[arg(long, value_name("PATH"), requires("corrupt"))]
+    pub corrupt_input: Option<std::path::PathBuf>,
+
+    /// Instead of raw data, write the content of the given file XORed byte for byte with the generated stream.
+    /// Applying this again to the result, with the same seed, gives back the original file.
+    #[arg(long, group("mode"), value_name("PATH"))]
+    pub xor_file: Option<std::path::PathBuf>,
+
+    /// Instead of raw data, write the given template file, where every placeholder {{rand:N}} is replaced
+    /// with N generated bytes, hex-encoded. The placeholders consume the generated stream in order.
+    #[arg(long, group("mode"), value_name("PATH"))]
+    pub template: Option<std::path::PathBuf>,
+
+    /// Instead of raw data, write the given number of pronounceable pseudo-words, separated by spaces.
+    /// Every word has 3 to 8 letters, alternating between consonants and vowels, starting with a consonant.
+    /// The letters are picked from the stream of the chain generator, so the generator options are rejected.
+    #[arg(
+        long,
+        group("mode"),
+        value_name("COUNT"),
+        conflicts_with_all(["generator", "hash", "counter_width"])
+    )]
+    pub words: Option<u64>,
+
+    /// Instead of raw data, write valid UTF-8 text of about --length bytes, where every character is a code
+    /// point of the Basic Multilingual Plane made of 2 generated bytes, skipping surrogates. Since characters
+    /// take 1 to 3 bytes, the text may be up to 2 bytes shorter than the length.
+    #[arg(long, group("mode"), requires("length"))]
+    pub utf8: bool,
+
+    /// Instead of raw data, write --length generated bytes as frames of records of --record-size bytes,
+    /// where every frame is the length of its record as a little-endian u32, followed by the record.
+    /// Only the last record may be shorter.
+    #[arg(long, group("mode"), requires_all(["length", "record_size"]))]
+    pub framed: bool,
+
+    /// Instead of raw data, write --length generated bytes as timed frames of records of --record-size bytes,
+    /// for replay tools that pace them: every frame is a delay in milliseconds as a little-endian u32, then
+    /// the length of its record as a little-endian u32, followed by the record. The delays are picked from
+    /// the generator, from 0 to --max-delay, and do not change the records, which are the generated stream.
+    #[arg(long, group("mode"), requires_all(["length", "record_size"]))]
+    pub inline_timing: bool,
+
+    /// The size of the records of --framed and --inline-timing
+    #[arg(
+        long,
+        value_name("BYTES"),
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..),
+        requires("frames")
+    )]
+    pub record_size: Option<u32>,
+
+    /// The largest delay of the frames of --inline-timing, in milliseconds
+    #[arg(
+        long,
+        value_name("MS"),
+        default_value_t = 1000,
+        requires("inline_timing")
+    )]
+    pub max_delay: u32,
+
+    /// Instead of raw data, write the given number of IP addresses, one per line. Every IPv4 address is made
+    /// of the next 4 generated bytes as its octets, and every IPv6 address of the next 16 bytes.
+    #[arg(long, group("mode"), value_name("COUNT"))]
+    pub ips: Option<u64>,
+
+    /// Instead of raw data, write a filesystem test image of the given total size, made of runs of data
+    /// and holes that alternate, starting with data. Every run has 1 to 16 blocks of 4096 bytes, picked
+    /// by the generator, and the data runs together are the generated stream. When writing to a file,
+    /// holes are skipped by seeking, which leaves real holes on filesystems that support them;
+    /// otherwise they are written as zeros.
+    #[arg(long, group("mode"), value_name("TOTAL"))]
+    pub fs_image: Option<u64>,
+
+    /// Instead of writing the output, listen for TCP connections on the given address, and serve the generated
+    /// stream to every client, from its start, with --length bytes, or without an end if no length is given.
+    #[cfg(feature = "listen")]
+    #[arg(long, group("mode"), value_name("ADDR:PORT"), conflicts_with_all(["output", "pipe_to"]))]
+    pub listen: Option<String>,
+
+    /// Instead of raw data, write the given number of times, one per line, picked uniformly in --date-range
+    /// with a resolution of a second, and formatted as RFC 3339 times in UTC
+    #[cfg(feature = "datetimes")]
+    #[arg(long, group("mode"), value_name("COUNT"), requires("date_range"))]
+    pub datetimes: Option<u64>,
+
+    /// The range of --datetimes, as START..END, where the start is included and the end is not, and both are
+    /// times in UTC, as 2024-01-01T00:00:00Z, or dates, as 2024-01-01
+    #[cfg(feature = "datetimes")]
+    #[arg(long, value_name("START..END"), requires("datetimes"))]
+    pub date_range: Option<crate::modes::datetimes::DateRange>,
+
+    /// With --ips, write IPv6 addresses instead of IPv4 addresses
+    #[arg(long, requires("ips"))]
+    pub ipv6: bool,
+
+    /// With --ips, follow every address with a port, made of the next 2 generated bytes in big-endian
+    #[arg(long, requires("ips"))]
+    pub ports: bool,
+
+    /// Instead of generating data, write the first COUNT internal 64-byte states of the hash chain
+    /// to stderr, hex-encoded, one per line, to compare against a reference implementation.
+    /// Nothing is written to stdout or the output file.
+    #[arg(long, group("mode"), value_name("COUNT"), conflicts_with_all(["output", "pipe_to"]))]
+    pub dump_states: Option<u64>,
+
+    /// Instead of generating data, write N child seeds derived from the seed, hex-encoded, one per line,
+    /// for sharded or multi-file workflows. Child `i` is the Blake2b hash of the seed followed by `i`
+    /// as a little-endian u64, and it is a 64-byte internal seed, to be used with --no-seed-hash.
+    #[arg(long, group("mode"), value_name("N"), conflicts_with("no_seed_hash"))]
+    pub derive_seeds: Option<usize>,
+
+    /// Instead of generating data, compare the given file with the generated stream by the Blake2b hashes
+    /// of their windows of --window bytes, and print the windows that differ. The exit code is 3
+    /// if any window differs.
+    #[arg(long, group("mode"), value_name("PATH"))]
+    pub compare_hashes: Option<std::path::PathBuf>,
+
+    /// The size of the windows of --compare-hashes
+    #[arg(
+        long,
+        value_name("BYTES"),
+        default_value_t = 1024 * 1024,
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..),
+        requires("compare_hashes")
+    )]
+    pub window: u64,
+
+    /// Instead of generating data, verify the files in the given directory, in the order of their names,
+    /// as an output that was split into files of --split-size bytes, and print whether every file passes.
+    /// With --length, the number of files and the length of the last one are checked too. The exit code is 3
+    /// if any file fails.
+    #[arg(long, group("mode"), value_name("DIR"), requires("split_size"))]
+    pub compare_dir: Option<std::path::PathBuf>,
+
+    /// The size of every file but the last one with --compare-dir
+    #[arg(
+        long,
+        value_name("BYTES"),
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..),
+        requires("compare_dir")
+    )]
+    pub split_size: Option<u64>,
+
+    /// Instead of raw data, write --records binary records with the given layout: a comma-separated list of
+    /// fields, where every field is one of u8, u16, u32, u64, u128 (little-endian integers) or bytes[N] (N raw bytes).
+    /// For example: u32,u16,bytes[10]. The fields are filled from the generated stream, one after the other.
+    #[arg(long, group("mode"), value_name("SPEC"), requires("records"))]
+    pub layout: Option<crate::modes::layout::Layout>,
+
+    /// The number of records to write with --layout
+    #[arg(long, value_name("COUNT"), requires("layout"))]
+    pub records: Option<u64>,
+
+    /// Fixed bytes, in hex, written between the records of --layout, which are otherwise written back to back.
+    /// Any bytes can be used, including NUL.
+    #[arg(long, value_name("HEX"), requires("layout"))]
+    pub separator: Option<HexBytes>,
+
+    /// Separate the records of --layout with a NUL byte, for tools such as `xargs -0`.
+    /// The same as `--separator 00`.
+    #[arg(long, requires("layout"), conflicts_with("separator"))]
+    pub null_separator: bool,
+
+    /// Instead of raw data, write --choices-count lines, each picked uniformly from the
+    /// newline-separated choices in the given file. Empty lines in the file are ignored.
+    #[arg(long, group("mode"), value_name("PATH"), requires("choices_count"))]
+    pub choices_file: Option<std::path::PathBuf>,
+
+    /// The number of choices to write with --choices-file
+    #[arg(long, value_name("COUNT"), requires("choices_file"))]
+    pub choices_count: Option<u64>,
+
+    /// Instead of raw data, write the lines of the given file in an order picked with the Fisher-Yates shuffle
+    /// from the generated stream, so that a seed always gives the same order. Every line is written with
+    /// a newline, including the last line of a file that does not end with one.
+    #[arg(long, group("mode"), value_name("PATH"))]
+    pub shuffle_lines: Option<std::path::PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Generator {
+    /// Every batch of 64 bytes is the Blake2b hash of the previous batch, starting from the hash of the seed
+    #[default]
+    Chain,
+    /// Every batch of 64 bytes is the Blake2b hash of the hashed seed and the batch index.
+    /// This allows computing any part of the stream without computing what comes before it.
+    Counter,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The generated bytes as they are
+    #[default]
+    Raw,
+    /// Lowercase hex, followed by a newline
+    Hex,
+    /// Base32 with the alphabet and padding of RFC 4648, followed by a newline
+    #[cfg(feature = "base32")]
+    Base32,
+    /// Base58 with the Bitcoin alphabet, in records of 32 bytes, one per line
+    #[cfg(feature = "base58")]
+    Base58,
+}
+
+/// The codecs of --roundtrip, with the default compression level
+#[cfg(feature = "compression")]
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// Raw deflate (RFC 1951)
+    Deflate,
+    /// Deflate in the zlib format (RFC 1950)
+    Zlib,
+    /// Deflate in the gzip format (RFC 1952)
+    Gzip,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashFunction {
+    #[default]
+    Blake2b,
+    /// Provided for interoperability with SHA-256 based references
+    #[cfg(feature = "sha256")]
+    Sha256,
+}
+
+/// Bytes provided as a hex string in the command line
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HexBytes(pub Vec<u8>);
+
+impl std::str::FromStr for HexBytes {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s).map(HexBytes)
+    }
+}
+
+/// A shard of the output, given as `INDEX/TOTAL` in the command line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    index: u64,
+    total: u64,
+}
+
+impl Shard {
+    /// The offset and the length of this shard in an output of the given length
+    pub fn range(&self, length: u64) -> (u64, u64) {
+        let boundary = |i: u64| (length as u128 * i as u128 / self.total as u128) as u64;
+        let start = boundary(self.index);
+        (start, boundary(self.index + 1) - start)
+    }
+}
+
+impl std::str::FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, total) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Expected INDEX/TOTAL, found: {s}"))?;
+        let index: u64 = index.parse().map_err(|e| format!("Invalid index: {e}"))?;
+        let total: u64 = total.parse().map_err(|e| format!("Invalid total: {e}"))?;
+        if index >= total {
+            return Err(format!(
+                "The index {index} must be smaller than the total {total}"
+            ));
+        }
+        Ok(Self { index, total })
+    }
+}
+
+fn parse_checkpoint_interval(s: &str) -> Result<u64, String> {
+    let bytes: u64 = s.parse().map_err(|e| format!("{e}"))?;
+    if bytes == 0 || bytes % 64 != 0 {
+        return Err(format!("{bytes} is not a positive multiple of 64"));
+    }
+    Ok(bytes)
+}
+
+fn parse_counter_width(s: &str) -> Result<CounterWidth, String> {
+    let bytes: usize = s.parse().map_err(|e| format!("{e}"))?;
+    CounterWidth::try_from(bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shard_parsing() {
+        assert_eq!(
+            "2/5".parse::<Shard>().unwrap(),
+            Shard { index: 2, total: 5 }
+        );
+        for invalid in ["", "1", "5/5", "0/0", "-1/2", "a/2", "1/2/3"] {
+            assert!(invalid.parse::<Shard>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn shard_ranges_cover_length() {
+        for (total, length) in [(1, 100), (3, 100), (7, 5), (4, 0)] {
+            let mut next_offset = 0;
+            for index in 0..total {
+                let (offset, shard_length) = Shard { index, total }.range(length);
+                assert_eq!(offset, next_offset);
+                assert!(shard_length.abs_diff(length / total) <= 1);
+                next_offset += shard_length;
+            }
+            assert_eq!(next_offset, length);
+        }
+    }
 }
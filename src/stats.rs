@@ -0,0 +1,142 @@
+use std::{io::Write, time::Instant};
+
+use crate::observers::Observer;
+
+/// How a generator hashes the blocks of its stream, which the hash evaluations are counted from
+#[derive(Clone, Copy)]
+pub enum Blocks {
+    /// A hash chain with batches of the given size, which computes every batch from the start of the stream,
+    /// including the ones before the start offset
+    Chain { batch_size: u64 },
+    /// A counter, which only computes the blocks of 64 bytes that the output covers
+    Counter,
+}
+
+/// Statistics about a run, reported with --stats
+pub struct RunStats {
+    start: Instant,
+    bytes: u64,
+    start_offset: u64,
+    blocks: Blocks,
+    seed_hashed: bool,
+}
+
+impl RunStats {
+    /// Start measuring a run of a generator that hashes the given blocks, with output from the given offset
+    /// of its stream. `seed_hashed` is whether the seed is hashed into the internal seed.
+    pub fn start(blocks: Blocks, start_offset: u64, seed_hashed: bool) -> Self {
+        Self {
+            start: Instant::now(),
+            bytes: 0,
+            start_offset,
+            blocks,
+            seed_hashed,
+        }
+    }
+
+    /// The number of hash evaluations of the blocks of the output so far, plus the hashing of the seed
+    fn hash_evaluations(&self) -> u64 {
+        let end = self.start_offset.saturating_add(self.bytes);
+        let blocks = match self.blocks {
+            Blocks::Chain { batch_size: 64 } => rdgen_lib::hash_evaluations(end),
+            Blocks::Chain { batch_size } => end.div_ceil(batch_size),
+            Blocks::Counter if self.bytes == 0 => 0,
+            Blocks::Counter => rdgen_lib::hash_evaluations(end) - self.start_offset / 64,
+        };
+        blocks + u64::from(self.seed_hashed)
+    }
+
+    /// Write the summary as `key: value` lines
+    pub fn write_report(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let throughput = if elapsed > 0. {
+            self.bytes as f64 / elapsed / (1024. * 1024.)
+        } else {
+            0.
+        };
+
+        writeln!(out, "bytes: {}", self.bytes)?;
+        writeln!(out, "wall time (s): {elapsed:.6}")?;
+        writeln!(out, "throughput (MiB/s): {throughput:.3}")?;
+        writeln!(out, "hash evaluations: {}", self.hash_evaluations())?;
+        Ok(())
+    }
+}
+
+impl Observer for RunStats {
+    fn observe(&mut self, chunk: &[u8]) {
+        self.bytes += chunk.len() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parseable_report() {
+        let mut stats = RunStats::start(Blocks::Chain { batch_size: 64 }, 0, true);
+        stats.observe(&[0; 64]);
+        stats.observe(&[0; 36]);
+
+        let mut report = Vec::new();
+        stats.write_report(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+
+        let fields: Vec<(&str, &str)> = report
+            .lines()
+            .map(|l| l.split_once(": ").unwrap())
+            .collect();
+        let keys: Vec<&str> = fields.iter().map(|(k, _)| *k).collect();
+        assert_eq!(
+            keys,
+            [
+                "bytes",
+                "wall time (s)",
+                "throughput (MiB/s)",
+                "hash evaluations"
+            ]
+        );
+        assert_eq!(fields[0].1, "100");
+        assert!(fields[1].1.parse::<f64>().unwrap() >= 0.);
+        assert!(fields[2].1.parse::<f64>().unwrap() >= 0.);
+        assert_eq!(fields[3].1, "3");
+    }
+
+    #[test]
+    fn empty_run() {
+        let mut report = Vec::new();
+        RunStats::start(Blocks::Chain { batch_size: 64 }, 0, true)
+            .write_report(&mut report)
+            .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("bytes: 0\n"));
+        assert!(report.ends_with("hash evaluations: 1\n"));
+    }
+
+    #[test]
+    fn hash_evaluations_follow_blocks() {
+        let evaluations = |blocks, start_offset, seed_hashed, length| {
+            let mut stats = RunStats::start(blocks, start_offset, seed_hashed);
+            stats.observe(&vec![0; length]);
+            stats.hash_evaluations()
+        };
+
+        let chain = Blocks::Chain { batch_size: 64 };
+        assert_eq!(evaluations(chain, 0, true, 64), 2);
+        // The seed is used as it is with --no-seed-hash
+        assert_eq!(evaluations(chain, 0, false, 64), 1);
+        // The chain computes the batches before the start offset too
+        assert_eq!(evaluations(chain, 32, true, 64), 3);
+        assert_eq!(evaluations(chain, 640, true, 64), 12);
+        assert_eq!(
+            evaluations(Blocks::Chain { batch_size: 32 }, 0, true, 100),
+            5
+        );
+
+        // The counter only computes the blocks that the output covers, here the first two
+        assert_eq!(evaluations(Blocks::Counter, 32, true, 64), 3);
+        assert_eq!(evaluations(Blocks::Counter, 640, true, 64), 2);
+        assert_eq!(evaluations(Blocks::Counter, 32, true, 0), 1);
+    }
+}
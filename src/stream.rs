@@ -0,0 +1,379 @@
+//! Sources of generated data, and adapters over streams of generated chunks of data.
+
+use rdgen_lib::{CounterDataWriter, FiniteDataWriter};
+
+/// The size of the buffer that generated data is written into before it is written to the output
+pub const GENERATION_BUFFER_SIZE: usize = 64 * 1024;
+
+const BATCH_SIZE: usize = 64;
+
+/// A source of generated data that writes into a buffer provided by the caller. The writers of the library
+/// write their batches directly into that buffer, so generating any length of data takes a fixed amount of memory.
+pub trait DataSource {
+    /// Write the next generated bytes into the start of the buffer, which must be at least 64 bytes,
+    /// and return their number. Zero means that the data has ended.
+    fn fill(&mut self, buffer: &mut [u8]) -> usize;
+}
+
+impl<S: DataSource + ?Sized> DataSource for Box<S> {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        (**self).fill(buffer)
+    }
+}
+
+/// Fill the buffer with whole batches for as long as the pulls give whole batches
+fn fill_with_batches(
+    buffer: &mut [u8],
+    mut pull_into: impl FnMut(&mut [u8; 64]) -> usize,
+) -> usize {
+    assert!(buffer.len() >= BATCH_SIZE, "The buffer must fit a batch");
+
+    let mut filled = 0;
+    for batch in buffer.chunks_exact_mut(BATCH_SIZE) {
+        let length = pull_into(batch.try_into().expect("Chunks are batches"));
+        filled += length;
+        if length < BATCH_SIZE {
+            break;
+        }
+    }
+    filled
+}
+
+impl DataSource for FiniteDataWriter {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        fill_with_batches(buffer, |batch| self.pull_into(batch))
+    }
+}
+
+impl DataSource for CounterDataWriter {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        fill_with_batches(buffer, |batch| self.pull_into(batch))
+    }
+}
+
+/// A data source over a stream of chunks, for generators that are combined with adapters
+pub struct Chunks<I> {
+    chunks: I,
+    pending: Vec<u8>,
+    consumed: usize,
+}
+
+impl<I: Iterator<Item = Vec<u8>>> Chunks<I> {
+    pub fn new(chunks: I) -> Self {
+        Self {
+            chunks,
+            pending: Vec::new(),
+            consumed: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Vec<u8>>> DataSource for Chunks<I> {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            if self.consumed == self.pending.len() {
+                match self.chunks.next() {
+                    Some(chunk) => self.pending = chunk,
+                    None => break,
+                }
+                self.consumed = 0;
+            }
+
+            let length = (buffer.len() - filled).min(self.pending.len() - self.consumed);
+            buffer[filled..filled + length]
+                .copy_from_slice(&self.pending[self.consumed..self.consumed + length]);
+            filled += length;
+            self.consumed += length;
+        }
+        filled
+    }
+}
+
+/// A reader of the data of a data source, for consumers that read the generated data like any other stream
+pub struct SourceReader<S> {
+    source: S,
+    buffer: Vec<u8>,
+    start: usize,
+    end: usize,
+}
+
+impl<S: DataSource> SourceReader<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            buffer: vec![0; GENERATION_BUFFER_SIZE],
+            start: 0,
+            end: 0,
+        }
+    }
+}
+
+impl<S: DataSource> std::io::Read for SourceReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.start == self.end {
+            self.start = 0;
+            self.end = self.source.fill(&mut self.buffer);
+        }
+        let length = buf.len().min(self.end - self.start);
+        buf[..length].copy_from_slice(&self.buffer[self.start..self.start + length]);
+        self.start += length;
+        Ok(length)
+    }
+}
+
+/// A data source that replaces the bytes at a fixed offset of another source with the given bytes,
+/// leaving the rest of the data as it is. If the data ends before the end of the overlay, the overlay is truncated.
+pub struct Overlay<S> {
+    source: S,
+    overlay: Vec<u8>,
+    offset: u64,
+    position: u64,
+}
+
+impl<S: DataSource> Overlay<S> {
+    pub fn new(source: S, overlay: Vec<u8>, offset: u64) -> Self {
+        Self {
+            source,
+            overlay,
+            offset,
+            position: 0,
+        }
+    }
+}
+
+impl<S: DataSource> DataSource for Overlay<S> {
+    fn fill(&mut self, buffer: &mut [u8]) -> usize {
+        let length = self.source.fill(buffer);
+        let filled = self.position..self.position + length as u64;
+        self.position = filled.end;
+
+        // The part of the overlay that falls in the filled range
+        let start = self.offset.max(filled.start);
+        // An overlay that would end past the largest offset is truncated there
+        let end = self
+            .offset
+            .saturating_add(self.overlay.len() as u64)
+            .min(filled.end);
+        if start < end {
+            let target = (start - filled.start) as usize..(end - filled.start) as usize;
+            let source = (start - self.offset) as usize..(end - self.offset) as usize;
+            buffer[target].copy_from_slice(&self.overlay[source]);
+        }
+        length
+    }
+}
+
+/// Limit a stream of chunks to the given total length, truncating the last chunk if needed.
+pub fn take_bytes(
+    chunks: impl Iterator<Item = Vec<u8>>,
+    length: u64,
+) -> impl Iterator<Item = Vec<u8>> {
+    chunks
+        .scan(length, |remaining, mut chunk| {
+            if *remaining == 0 {
+                return None;
+            }
+            chunk.truncate((*remaining).min(chunk.len() as u64) as usize);
+            *remaining -= chunk.len() as u64;
+            Some(chunk)
+        })
+        .filter(|chunk| !chunk.is_empty())
+}
+
+/// Drop the given number of bytes from the start of a stream of chunks.
+pub fn skip_bytes(
+    chunks: impl Iterator<Item = Vec<u8>>,
+    length: u64,
+) -> impl Iterator<Item = Vec<u8>> {
+    chunks
+        .scan(length, |remaining, mut chunk| {
+            let skipped = (*remaining).min(chunk.len() as u64) as usize;
+            chunk.drain(..skipped);
+            *remaining -= skipped as u64;
+            Some(chunk)
+        })
+        .filter(|chunk| !chunk.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+
+    use super::*;
+
+    /// Counts the bytes allocated by every thread, so that tests can check the allocations of the code they run
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATED.try_with(|a| a.set(a.get() + layout.size()));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocated_by(f: impl FnOnce()) -> usize {
+        let before = ALLOCATED.with(|a| a.get());
+        f();
+        ALLOCATED.with(|a| a.get()) - before
+    }
+
+    /// Drain the source into the buffer, and return the total length of the data
+    fn drain(source: &mut impl DataSource, buffer: &mut [u8]) -> u64 {
+        let mut total = 0;
+        loop {
+            let length = source.fill(buffer);
+            if length == 0 {
+                return total;
+            }
+            total += length as u64;
+        }
+    }
+
+    #[test]
+    fn sources_match_iterators() {
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(10_001))
+            .flatten()
+            .collect();
+
+        let mut counter = CounterDataWriter::new("abc", Some(10_001 - 70));
+        counter.seek_to(70).unwrap();
+        let expected_counter: Vec<u8> = CounterDataWriter::new("abc", Some(10_001))
+            .flatten()
+            .skip(70)
+            .collect();
+
+        let sources: Vec<(Box<dyn DataSource>, &[u8])> = vec![
+            (
+                Box::new(FiniteDataWriter::new("abc", Some(10_001))),
+                &expected,
+            ),
+            (
+                Box::new(Chunks::new(FiniteDataWriter::new("abc", Some(10_001)))),
+                &expected,
+            ),
+            (Box::new(counter), &expected_counter),
+        ];
+        for (mut source, expected) in sources {
+            let mut data = Vec::new();
+            let mut buffer = [0; 1000];
+            loop {
+                let length = source.fill(&mut buffer);
+                if length == 0 {
+                    break;
+                }
+                data.extend_from_slice(&buffer[..length]);
+            }
+            assert_eq!(data, expected);
+        }
+    }
+
+    #[test]
+    fn overlay_replaces_bytes() {
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(300)).flatten().collect();
+        let serial = 0x0102_0304_0506_0708u64;
+
+        for (offset, buffer_size) in [
+            (0, 64),
+            (60, 64),
+            (100, 1000),
+            (296, 128),
+            (400, 64),
+            (u64::MAX, 64),
+        ] {
+            let mut source = Overlay::new(
+                FiniteDataWriter::new("abc", Some(300)),
+                serial.to_le_bytes().to_vec(),
+                offset,
+            );
+            let mut data = Vec::new();
+            let mut buffer = vec![0; buffer_size];
+            loop {
+                let length = source.fill(&mut buffer);
+                if length == 0 {
+                    break;
+                }
+                data.extend_from_slice(&buffer[..length]);
+            }
+
+            let start = offset.min(300) as usize;
+            let end = (start + 8).min(300);
+            assert_eq!(data[..start], expected[..start]);
+            assert_eq!(data[start..end], serial.to_le_bytes()[..end - start]);
+            assert_eq!(data[end..], expected[end..]);
+        }
+    }
+
+    #[test]
+    fn source_reader_reads_source() {
+        use std::io::Read;
+
+        let expected: Vec<u8> = FiniteDataWriter::new("abc", Some(100_001))
+            .flatten()
+            .collect();
+        let mut data = Vec::new();
+        SourceReader::new(FiniteDataWriter::new("abc", Some(100_001)))
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn bounded_memory() {
+        const LENGTH: u64 = 4 * 1024 * 1024;
+
+        let mut buffer = vec![0; GENERATION_BUFFER_SIZE];
+        let mut total = 0;
+        let allocated = allocated_by(|| {
+            let mut chain = FiniteDataWriter::new("abc", Some(LENGTH));
+            total += drain(&mut chain, &mut buffer);
+            let mut counter = CounterDataWriter::new("abc", Some(LENGTH));
+            total += drain(&mut counter, &mut buffer);
+        });
+
+        assert_eq!(total, 2 * LENGTH);
+        // Generating does not allocate at all, no matter the length
+        assert_eq!(allocated, 0);
+    }
+
+    fn chunks() -> impl Iterator<Item = Vec<u8>> {
+        (0..10u8).map(|i| (i * 10..i * 10 + 10).collect())
+    }
+
+    #[test]
+    fn take_bytes_truncates() {
+        for length in [0, 1, 9, 10, 11, 55, 100, 1000] {
+            let data: Vec<u8> = take_bytes(chunks(), length).flatten().collect();
+            assert_eq!(data.len() as u64, length.min(100));
+            assert_eq!(
+                data,
+                chunks().flatten().take(length as usize).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn skip_bytes_drops_prefix() {
+        for length in [0, 1, 9, 10, 11, 55, 100, 1000] {
+            let data: Vec<u8> = skip_bytes(chunks(), length).flatten().collect();
+            assert_eq!(
+                data,
+                chunks().flatten().skip(length as usize).collect::<Vec<_>>()
+            );
+        }
+    }
+}